@@ -0,0 +1,46 @@
+//! Embeds build metadata reported by `GET /version`: the commit from
+//! `COMMIT_SHA` (set in CI, or written to `.env` by prep.sh), the build time
+//! and the enabled cargo features.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=COMMIT_SHA");
+    // A missing file counts as changed on every build, so only watch it when
+    // prep.sh has written it.
+    if std::path::Path::new(".env").exists() {
+        println!("cargo:rerun-if-changed=.env");
+    }
+
+    let commit = std::env::var("COMMIT_SHA")
+        .ok()
+        .or_else(commit_from_dotenv)
+        .filter(|commit| !commit.is_empty())
+        .unwrap_or_else(|| "unknown".into());
+    println!("cargo:rustc-env=COMMIT_SHA={commit}");
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    println!("cargo:rustc-env=BUILD_TIMESTAMP={timestamp}");
+
+    let mut features: Vec<String> = std::env::vars()
+        .filter_map(|(name, _)| {
+            name.strip_prefix("CARGO_FEATURE_")
+                .map(|feature| feature.to_lowercase().replace('_', "-"))
+        })
+        .collect();
+    features.sort();
+    println!("cargo:rustc-env=BUILD_FEATURES={}", features.join(","));
+}
+
+fn commit_from_dotenv() -> Option<String> {
+    let contents = std::fs::read_to_string(".env").ok()?;
+    contents.lines().find_map(|line| {
+        line.trim()
+            .strip_prefix("COMMIT_SHA=")
+            .map(|commit| commit.trim().to_string())
+    })
+}
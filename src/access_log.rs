@@ -0,0 +1,201 @@
+//! Request IDs and access logging.
+//!
+//! Every request gets an ID: the caller's `X-Request-Id` when it is a
+//! plausible ID, otherwise a fresh random one. The ID is echoed in the
+//! response header and in error bodies, and the fairing prints one JSON line
+//! per request to stdout so support can match a report to its log line.
+
+use crate::auth;
+use alloy::primitives::FixedBytes;
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Header;
+use rocket::{Data, Request, Response};
+use serde::Serialize;
+use std::time::Instant;
+
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+/// Longest caller-supplied ID that is propagated rather than replaced.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+struct RequestContext {
+    id: String,
+    started: Instant,
+}
+
+/// The ID assigned to `req`, if the fairing is attached.
+pub fn request_id<'r>(req: &'r Request<'_>) -> Option<&'r str> {
+    req.local_cache(|| None::<RequestContext>)
+        .as_ref()
+        .map(|context| context.id.as_str())
+}
+
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+}
+
+fn new_request_id() -> String {
+    alloy::hex::encode(FixedBytes::<16>::random())
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AccessLogLine<'a> {
+    request_id: &'a str,
+    method: &'a str,
+    /// Matched route template; absent when no route matched.
+    route: Option<&'a str>,
+    path: &'a str,
+    status: u16,
+    latency_ms: u64,
+    /// Name of the API key that authenticated the request.
+    api_key: Option<&'a str>,
+    client_ip: Option<String>,
+}
+
+pub struct AccessLog;
+
+pub fn fairing() -> AccessLog {
+    AccessLog
+}
+
+#[rocket::async_trait]
+impl Fairing for AccessLog {
+    fn info(&self) -> Info {
+        Info {
+            name: "Access log",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _: &mut Data<'_>) {
+        let id = req
+            .headers()
+            .get_one(REQUEST_ID_HEADER)
+            .filter(|id| is_valid_request_id(id))
+            .map_or_else(new_request_id, String::from);
+        req.local_cache(|| {
+            Some(RequestContext {
+                id,
+                started: Instant::now(),
+            })
+        });
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let Some(context) = req.local_cache(|| None::<RequestContext>) else {
+            return;
+        };
+        res.set_header(Header::new(REQUEST_ID_HEADER, context.id.clone()));
+        let line = AccessLogLine {
+            request_id: &context.id,
+            method: req.method().as_str(),
+            route: req.route().map(|route| route.uri.as_str()),
+            path: req.uri().path().as_str(),
+            status: res.status().code,
+            latency_ms: context.started.elapsed().as_millis() as u64,
+            api_key: auth::key_name(req),
+            client_ip: req.client_ip().map(|ip| ip.to_string()),
+        };
+        if let Ok(line) = serde_json::to_string(&line) {
+            println!("{line}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ApiError;
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+
+    #[get("/missing")]
+    fn missing() -> Result<(), ApiError> {
+        Err(ApiError::NotFound("order not found".into()))
+    }
+
+    fn client() -> Client {
+        let rocket = rocket::build()
+            .mount("/", rocket::routes![missing])
+            .register("/", crate::error::catchers())
+            .attach(fairing());
+        Client::tracked(rocket).expect("valid rocket instance")
+    }
+
+    #[test]
+    fn test_request_id_validation() {
+        assert!(is_valid_request_id("req-123_abc.def:1"));
+        assert!(!is_valid_request_id(""));
+        assert!(!is_valid_request_id("has space"));
+        assert!(!is_valid_request_id(&"a".repeat(MAX_REQUEST_ID_LEN + 1)));
+        assert_eq!(new_request_id().len(), 32);
+    }
+
+    #[test]
+    fn test_propagates_caller_request_id_into_errors() {
+        let client = client();
+        let response = client
+            .get("/missing")
+            .header(Header::new(REQUEST_ID_HEADER, "support-ticket-42"))
+            .dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        assert_eq!(
+            response.headers().get_one(REQUEST_ID_HEADER),
+            Some("support-ticket-42")
+        );
+        let body: serde_json::Value =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(body["error"]["requestId"], "support-ticket-42");
+    }
+
+    #[test]
+    fn test_replaces_invalid_request_id_in_catcher_errors() {
+        let client = client();
+        let response = client
+            .get("/v1/nope")
+            .header(Header::new(REQUEST_ID_HEADER, "bad id\"}"))
+            .dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        let id = response
+            .headers()
+            .get_one(REQUEST_ID_HEADER)
+            .unwrap()
+            .to_string();
+        assert!(is_valid_request_id(&id));
+        assert_ne!(id, "bad id\"}");
+        let body: serde_json::Value =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(body["error"]["requestId"], id);
+    }
+
+    #[test]
+    fn test_access_log_line_shape() {
+        let line = AccessLogLine {
+            request_id: "abc",
+            method: "GET",
+            route: Some("/v1/order/<order_hash>"),
+            path: "/v1/order/0x00",
+            status: 404,
+            latency_ms: 3,
+            api_key: Some("test"),
+            client_ip: Some("127.0.0.1".into()),
+        };
+        assert_eq!(
+            serde_json::to_value(&line).unwrap(),
+            serde_json::json!({
+                "requestId": "abc",
+                "method": "GET",
+                "route": "/v1/order/<order_hash>",
+                "path": "/v1/order/0x00",
+                "status": 404,
+                "latencyMs": 3,
+                "apiKey": "test",
+                "clientIp": "127.0.0.1",
+            })
+        );
+    }
+}
@@ -0,0 +1,334 @@
+//! API key authentication.
+//!
+//! Keys are presented as `Authorization: Bearer <key>` or `X-API-Key: <key>`
+//! and checked against the keccak256 hashes in [`AppConfig::api_keys`], so
+//! plaintext keys never live in configuration. Each key carries the scopes it
+//! may use; handlers require one through an [`Authorized`] request guard.
+
+use crate::config::AppConfig;
+use crate::error::ApiError;
+use crate::rate_limit::{self, RateLimiter};
+use alloy::primitives::{keccak256, B256};
+use rocket::fairing::AdHoc;
+use rocket::request::{FromRequest, Outcome, Request};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// Order and trade lookups.
+    Read,
+    /// Swap quotes and calldata.
+    Quote,
+    /// Order deployment and cancellation.
+    Deploy,
+    /// Operational endpoints such as `/metrics`.
+    Admin,
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Scope::Read => "read",
+            Scope::Quote => "quote",
+            Scope::Deploy => "deploy",
+            Scope::Admin => "admin",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyEntry {
+    /// Who the key was issued to; appears in error messages, never the key.
+    pub name: String,
+    /// keccak256 of the plaintext key.
+    pub key_hash: B256,
+    pub scopes: Vec<Scope>,
+    /// Rate limit tier from the `rate_limits` setting.
+    #[serde(default = "default_tier")]
+    pub tier: String,
+}
+
+fn default_tier() -> String {
+    DEFAULT_TIER.to_string()
+}
+
+pub const DEFAULT_TIER: &str = "standard";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ApiKeyStoreError {
+    #[error("duplicate API key hash {0}")]
+    DuplicateKey(B256),
+}
+
+#[derive(Debug, Default)]
+pub struct ApiKeyStore {
+    keys: HashMap<B256, ApiKeyEntry>,
+}
+
+impl ApiKeyStore {
+    pub fn new(entries: Vec<ApiKeyEntry>) -> Result<Self, ApiKeyStoreError> {
+        let mut keys = HashMap::new();
+        for entry in entries {
+            let hash = entry.key_hash;
+            if keys.insert(hash, entry).is_some() {
+                return Err(ApiKeyStoreError::DuplicateKey(hash));
+            }
+        }
+        Ok(Self { keys })
+    }
+
+    pub fn hash_key(key: &str) -> B256 {
+        keccak256(key.as_bytes())
+    }
+
+    pub fn authenticate(&self, key: &str) -> Option<&ApiKeyEntry> {
+        self.keys.get(&Self::hash_key(key))
+    }
+
+    /// Resolves the request's key, charges its rate limit and checks it
+    /// grants `scope`. Requests without a valid key are limited by IP, so
+    /// rejected keys still count.
+    fn authorize(
+        &self,
+        req: &Request<'_>,
+        limiter: Option<&RateLimiter>,
+        scope: Scope,
+    ) -> Result<(), ApiError> {
+        let key = presented_key(req);
+        let entry = key.and_then(|key| self.authenticate(key));
+        req.local_cache(|| AuthenticatedKey(entry.map(|entry| entry.name.clone())));
+        if let Some(limiter) = limiter {
+            let (client, tier) = match entry {
+                Some(entry) => (rate_limit::Client::Key(entry.key_hash), entry.tier.as_str()),
+                None => (
+                    rate_limit::Client::Ip(req.client_ip()),
+                    rate_limit::ANONYMOUS_TIER,
+                ),
+            };
+            limiter.check_request(req, client, tier, scope)?;
+        }
+        let entry = match (key, entry) {
+            (_, Some(entry)) => entry,
+            (None, None) => {
+                return Err(ApiError::Unauthorized(
+                    "missing API key; send Authorization: Bearer <key> or X-API-Key".into(),
+                ))
+            }
+            (Some(_), None) => return Err(ApiError::Unauthorized("invalid API key".into())),
+        };
+        if !entry.scopes.contains(&scope) {
+            return Err(ApiError::Forbidden(format!(
+                "API key {} lacks the {scope} scope",
+                entry.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Name of the key that authenticated the request.
+struct AuthenticatedKey(Option<String>);
+
+/// The name of the API key `req` authenticated with, once an [`Authorized`]
+/// guard has run.
+pub fn key_name<'r>(req: &'r Request<'_>) -> Option<&'r str> {
+    req.local_cache(|| AuthenticatedKey(None)).0.as_deref()
+}
+
+fn presented_key<'r>(req: &'r Request<'_>) -> Option<&'r str> {
+    let headers = req.headers();
+    headers.get_one("X-API-Key").or_else(|| {
+        headers
+            .get_one("Authorization")
+            .and_then(|value| value.strip_prefix("Bearer "))
+    })
+}
+
+pub fn fairing() -> AdHoc {
+    AdHoc::try_on_ignite("API keys", |rocket| async move {
+        let Some(config) = rocket.state::<AppConfig>() else {
+            error!("API keys need the configuration fairing");
+            return Err(rocket);
+        };
+        match ApiKeyStore::new(config.api_keys.clone()) {
+            Ok(store) => Ok(rocket.manage(store)),
+            Err(e) => {
+                error!("invalid API key settings: {}", e);
+                Err(rocket)
+            }
+        }
+    })
+}
+
+/// Marks the scope an [`Authorized`] guard requires.
+pub trait RequiredScope: Send + Sync + 'static {
+    const SCOPE: Scope;
+}
+
+pub struct ReadScope;
+pub struct QuoteScope;
+pub struct DeployScope;
+pub struct AdminScope;
+
+impl RequiredScope for ReadScope {
+    const SCOPE: Scope = Scope::Read;
+}
+
+impl RequiredScope for QuoteScope {
+    const SCOPE: Scope = Scope::Quote;
+}
+
+impl RequiredScope for DeployScope {
+    const SCOPE: Scope = Scope::Deploy;
+}
+
+impl RequiredScope for AdminScope {
+    const SCOPE: Scope = Scope::Admin;
+}
+
+/// A request whose API key grants `S`. Handlers take
+/// `Result<Authorized<S>, ApiError>` so a rejection is rendered as an
+/// [`ApiError`] rather than through a catcher.
+#[derive(Debug)]
+pub struct Authorized<S>(PhantomData<S>);
+
+#[rocket::async_trait]
+impl<'r, S: RequiredScope> FromRequest<'r> for Authorized<S> {
+    type Error = ApiError;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let result = match req.rocket().state::<ApiKeyStore>() {
+            Some(store) => store.authorize(req, req.rocket().state(), S::SCOPE),
+            None => Err(ApiError::Internal("API key store is not configured".into())),
+        };
+        match result {
+            Ok(()) => Outcome::Success(Authorized(PhantomData)),
+            Err(e) => Outcome::Error((e.status(), e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::http::{Header, Status};
+    use rocket::local::blocking::Client;
+
+    #[get("/deploy")]
+    fn deploy(auth: Result<Authorized<DeployScope>, ApiError>) -> Result<&'static str, ApiError> {
+        auth?;
+        Ok("ok")
+    }
+
+    fn entry(name: &str, key: &str, scopes: Vec<Scope>) -> ApiKeyEntry {
+        ApiKeyEntry {
+            name: name.into(),
+            key_hash: ApiKeyStore::hash_key(key),
+            scopes,
+            tier: DEFAULT_TIER.into(),
+        }
+    }
+
+    fn client() -> Client {
+        let store = ApiKeyStore::new(vec![
+            entry("partner", "partner-key", vec![Scope::Read, Scope::Quote]),
+            entry("deployer", "deploy-key", vec![Scope::Deploy]),
+        ])
+        .unwrap();
+        let rocket = rocket::build()
+            .mount("/", rocket::routes![deploy])
+            .register("/", crate::error::catchers())
+            .manage(store);
+        Client::tracked(rocket).expect("valid rocket instance")
+    }
+
+    fn code(response: rocket::local::blocking::LocalResponse<'_>) -> (Status, String) {
+        let status = response.status();
+        let body: serde_json::Value =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        (status, body["error"]["code"].as_str().unwrap().to_string())
+    }
+
+    #[test]
+    fn test_store_rejects_duplicate_keys() {
+        let result = ApiKeyStore::new(vec![
+            entry("a", "same", vec![Scope::Read]),
+            entry("b", "same", vec![Scope::Deploy]),
+        ]);
+        assert!(matches!(result, Err(ApiKeyStoreError::DuplicateKey(_))));
+    }
+
+    #[test]
+    fn test_store_authenticates_by_hash() {
+        let store = ApiKeyStore::new(vec![entry("a", "secret", vec![Scope::Read])]).unwrap();
+        assert_eq!(store.authenticate("secret").unwrap().name, "a");
+        assert!(store.authenticate("Secret").is_none());
+    }
+
+    #[test]
+    fn test_accepts_bearer_and_api_key_headers() {
+        let client = client();
+        let response = client
+            .get("/deploy")
+            .header(Header::new("Authorization", "Bearer deploy-key"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let response = client
+            .get("/deploy")
+            .header(Header::new("X-API-Key", "deploy-key"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+    }
+
+    #[test]
+    fn test_missing_or_invalid_key_is_unauthorized() {
+        let client = client();
+        let (status, code) = code(client.get("/deploy").dispatch());
+        assert_eq!(
+            (status, code.as_str()),
+            (Status::Unauthorized, "UNAUTHORIZED")
+        );
+        let response = client
+            .get("/deploy")
+            .header(Header::new("Authorization", "Basic deploy-key"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+        let response = client
+            .get("/deploy")
+            .header(Header::new("X-API-Key", "wrong"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+    }
+
+    #[test]
+    fn test_records_authenticated_key_name() {
+        let client = client();
+        let store = client.rocket().state::<ApiKeyStore>().unwrap();
+        let request = client
+            .get("/deploy")
+            .header(Header::new("X-API-Key", "partner-key"));
+        assert!(store.authorize(request.inner(), None, Scope::Read).is_ok());
+        assert_eq!(key_name(request.inner()), Some("partner"));
+
+        let anonymous = client.get("/deploy");
+        assert!(store
+            .authorize(anonymous.inner(), None, Scope::Read)
+            .is_err());
+        assert_eq!(key_name(anonymous.inner()), None);
+    }
+
+    #[test]
+    fn test_missing_scope_is_forbidden() {
+        let client = client();
+        let response = client
+            .get("/deploy")
+            .header(Header::new("X-API-Key", "partner-key"))
+            .dispatch();
+        let (status, code) = code(response);
+        assert_eq!((status, code.as_str()), (Status::Forbidden, "FORBIDDEN"));
+    }
+}
@@ -0,0 +1,84 @@
+//! Short-lived caching for upstream results that are costly to recompute.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Values kept for `ttl` after they are stored. Once `capacity` entries are
+/// held, expired ones are dropped to make room, and if none have expired the
+/// cache starts over rather than growing.
+pub struct TtlCache<K, V> {
+    ttl: Duration,
+    capacity: usize,
+    entries: Mutex<HashMap<K, (Instant, V)>>,
+}
+
+impl<K: Eq + Hash, V: Clone> TtlCache<K, V> {
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.get_at(key, Instant::now())
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        self.insert_at(key, value, Instant::now())
+    }
+
+    fn get_at(&self, key: &K, now: Instant) -> Option<V> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(key)
+            .filter(|(stored, _)| now.duration_since(*stored) < self.ttl)
+            .map(|(_, value)| value.clone())
+    }
+
+    fn insert_at(&self, key: K, value: V, now: Instant) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            entries.retain(|_, (stored, _)| now.duration_since(*stored) < self.ttl);
+            if entries.len() >= self.capacity {
+                entries.clear();
+            }
+        }
+        entries.insert(key, (now, value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_expire_after_ttl() {
+        let cache = TtlCache::new(Duration::from_secs(30), 10);
+        let start = Instant::now();
+        cache.insert_at("a", 1, start);
+        assert_eq!(cache.get_at(&"a", start + Duration::from_secs(29)), Some(1));
+        assert_eq!(cache.get_at(&"a", start + Duration::from_secs(30)), None);
+        assert_eq!(cache.get_at(&"b", start), None);
+    }
+
+    #[test]
+    fn test_full_cache_drops_expired_entries_first() {
+        let cache = TtlCache::new(Duration::from_secs(30), 2);
+        let start = Instant::now();
+        cache.insert_at("old", 1, start);
+        cache.insert_at("live", 2, start + Duration::from_secs(20));
+        let later = start + Duration::from_secs(40);
+        cache.insert_at("new", 3, later);
+        assert_eq!(cache.get_at(&"live", later), Some(2));
+        assert_eq!(cache.get_at(&"new", later), Some(3));
+
+        // With nothing expired, the cache starts over instead of growing.
+        cache.insert_at("newest", 4, later);
+        assert_eq!(cache.get_at(&"live", later), None);
+        assert_eq!(cache.get_at(&"newest", later), Some(4));
+    }
+}
@@ -0,0 +1,292 @@
+//! Typed application configuration.
+//!
+//! Settings come from the active profile of Rocket.toml, then `ROCKET_` and
+//! finally `ST0X_` environment variables. Nested keys are separated by `__`,
+//! so `ST0X_DEPLOYMENT__ORDERBOOK` overrides `deployment.orderbook`. The
+//! configuration is validated once at ignition and managed as [`AppConfig`];
+//! the other fairings build their state from it.
+
+use crate::auth::ApiKeyEntry;
+use crate::cors::CorsSettings;
+use crate::health::HealthSettings;
+use crate::metrics::MetricsSettings;
+use crate::rate_limit::Tiers;
+use crate::strategy::Deployment;
+use alloy::primitives::{keccak256, B256};
+use rocket::fairing::AdHoc;
+use rocket::figment::providers::Env;
+use rocket::figment::Figment;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Prefix of environment variables that override configuration.
+pub const ENV_PREFIX: &str = "ST0X_";
+const DEFAULT_REGISTRY_PATH: &str = "tokens.toml";
+const DEFAULT_CHAIN_ID: u64 = 8453;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppConfig {
+    #[serde(default = "default_chain_id")]
+    pub chain_id: u64,
+    /// Token registry TOML, relative to the working directory.
+    #[serde(default = "default_registry_path")]
+    pub token_registry: PathBuf,
+    /// Orderbook subgraph queried for orders, trades and indexing progress.
+    pub subgraph_url: String,
+    /// JSON-RPC endpoint for quotes, parsing and transaction lookups.
+    pub rpc_url: String,
+    pub deployment: Deployment,
+    #[serde(default)]
+    pub api_keys: Vec<ApiKeyEntry>,
+    #[serde(default)]
+    pub rate_limits: Tiers,
+    #[serde(default)]
+    pub cors: CorsSettings,
+    #[serde(default)]
+    pub health: HealthSettings,
+    #[serde(default)]
+    pub metrics: MetricsSettings,
+}
+
+fn default_chain_id() -> u64 {
+    DEFAULT_CHAIN_ID
+}
+
+fn default_registry_path() -> PathBuf {
+    PathBuf::from(DEFAULT_REGISTRY_PATH)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("chain_id must not be 0")]
+    ZeroChainId,
+    #[error("{field} must be an http(s) URL, got {value:?}")]
+    InvalidUrl { field: &'static str, value: String },
+    #[error("deployment.{0} must not be the zero address")]
+    ZeroAddress(&'static str),
+    #[error("API key {name} uses rate limit tier {tier} which is not configured")]
+    UnknownTier { name: String, tier: String },
+    #[error("rate_limits.{tier}.{group} must have a capacity of at least 1")]
+    ZeroCapacity { tier: String, group: String },
+    #[error("health.probe_timeout_ms must be at least 1")]
+    ZeroProbeTimeout,
+}
+
+impl AppConfig {
+    /// Checks what deserialization cannot, reporting every problem found.
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+        if self.chain_id == 0 {
+            errors.push(ConfigError::ZeroChainId);
+        }
+        for (field, value) in [
+            ("subgraph_url", &self.subgraph_url),
+            ("rpc_url", &self.rpc_url),
+        ] {
+            let valid = reqwest::Url::parse(value)
+                .is_ok_and(|url| matches!(url.scheme(), "http" | "https"));
+            if !valid {
+                errors.push(ConfigError::InvalidUrl {
+                    field,
+                    value: value.clone(),
+                });
+            }
+        }
+        let deployment = &self.deployment;
+        for (field, address) in [
+            ("orderbook", deployment.orderbook),
+            ("deployer", deployment.deployer),
+            ("interpreter", deployment.interpreter),
+            ("store", deployment.store),
+            ("sub_parser", deployment.sub_parser),
+        ] {
+            if address.is_zero() {
+                errors.push(ConfigError::ZeroAddress(field));
+            }
+        }
+        for entry in &self.api_keys {
+            if !self.rate_limits.contains_key(&entry.tier) {
+                errors.push(ConfigError::UnknownTier {
+                    name: entry.name.clone(),
+                    tier: entry.tier.clone(),
+                });
+            }
+        }
+        for (tier, groups) in &self.rate_limits {
+            for (group, limit) in groups {
+                if limit.capacity == 0 {
+                    errors.push(ConfigError::ZeroCapacity {
+                        tier: tier.clone(),
+                        group: group.to_string(),
+                    });
+                }
+            }
+        }
+        if self.health.probe_timeout_ms == 0 {
+            errors.push(ConfigError::ZeroProbeTimeout);
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Hash identifying this configuration, to tell deployments apart
+    /// without revealing endpoints. Stable across restarts: object keys
+    /// serialize in sorted order.
+    pub fn fingerprint(&self) -> B256 {
+        let canonical = serde_json::to_value(self)
+            .map(|value| value.to_string())
+            .unwrap_or_default();
+        keccak256(canonical.as_bytes())
+    }
+}
+
+/// Rocket's figment with `ST0X_` environment overrides on top.
+pub fn figment() -> Figment {
+    with_env_overrides(rocket::Config::figment())
+}
+
+fn with_env_overrides(figment: Figment) -> Figment {
+    figment.merge(Env::prefixed(ENV_PREFIX).split("__").global())
+}
+
+/// Loads and validates [`AppConfig`]. Attach before every fairing that reads it.
+pub fn fairing() -> AdHoc {
+    AdHoc::try_on_ignite("Configuration", |rocket| async move {
+        let config: AppConfig = match rocket.figment().extract() {
+            Ok(config) => config,
+            Err(e) => {
+                error!("invalid configuration: {}", e);
+                return Err(rocket);
+            }
+        };
+        if let Err(errors) = config.validate() {
+            for e in errors {
+                error!("invalid configuration: {}", e);
+            }
+            return Err(rocket);
+        }
+        Ok(rocket.manage(config))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source::memory;
+    use crate::rate_limit::Limit;
+    use alloy::primitives::Address;
+    use rocket::figment::providers::{Format, Toml};
+    use rocket::figment::Jail;
+    use std::collections::HashMap;
+
+    const MINIMAL: &str = r#"
+        subgraph_url = "https://example.com/subgraph"
+        rpc_url = "https://example.com/rpc"
+
+        [deployment]
+        orderbook = "0x1111111111111111111111111111111111111111"
+        deployer = "0x2222222222222222222222222222222222222222"
+        interpreter = "0x3333333333333333333333333333333333333333"
+        store = "0x4444444444444444444444444444444444444444"
+        sub_parser = "0x5555555555555555555555555555555555555555"
+    "#;
+
+    #[test]
+    fn test_minimal_config_uses_defaults() {
+        let config: AppConfig = Figment::from(Toml::string(MINIMAL)).extract().unwrap();
+        assert_eq!(config.chain_id, DEFAULT_CHAIN_ID);
+        assert_eq!(config.token_registry, PathBuf::from("tokens.toml"));
+        assert!(config.api_keys.is_empty());
+        assert!(config.cors.allowed_origins.is_empty());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_rocket_toml_debug_profile_is_valid() {
+        let config: AppConfig = figment().extract().unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.api_keys.len(), 1);
+    }
+
+    #[test]
+    fn test_client_ip_header_is_not_trusted_by_default() {
+        let config: rocket::Config = figment().extract().unwrap();
+        assert_eq!(config.ip_header, None);
+    }
+
+    #[test]
+    // `Jail` fixes the closure's error type to figment's own.
+    #[allow(clippy::result_large_err)]
+    fn test_env_overrides_split_nested_keys() {
+        Jail::expect_with(|jail| {
+            jail.set_env("ST0X_CONFIG_TEST__NESTED_KEY", "from-env");
+            let figment = with_env_overrides(Figment::from(Toml::string(MINIMAL)));
+            let value: String = figment.extract_inner("config_test.nested_key")?;
+            assert_eq!(value, "from-env");
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_fingerprint_tracks_configuration() {
+        let config = memory::config();
+        assert_eq!(config.fingerprint(), memory::config().fingerprint());
+        let other = AppConfig {
+            chain_id: 1,
+            ..memory::config()
+        };
+        assert_ne!(config.fingerprint(), other.fingerprint());
+    }
+
+    #[test]
+    fn test_validate_reports_every_problem() {
+        let mut config = memory::config();
+        config.chain_id = 0;
+        config.rpc_url = "ws://localhost:8545".into();
+        config.deployment.store = Address::ZERO;
+        config.rate_limits = HashMap::from([(
+            "standard".to_string(),
+            HashMap::from([(
+                crate::auth::Scope::Read,
+                Limit {
+                    capacity: 0,
+                    per_minute: 1,
+                },
+            )]),
+        )]);
+        config.api_keys[0].tier = "premium".into();
+        config.health.probe_timeout_ms = 0;
+        let errors: Vec<_> = config
+            .validate()
+            .unwrap_err()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "chain_id must not be 0",
+                "rpc_url must be an http(s) URL, got \"ws://localhost:8545\"",
+                "deployment.store must not be the zero address",
+                "API key test uses rate limit tier premium which is not configured",
+                "rate_limits.standard.read must have a capacity of at least 1",
+                "health.probe_timeout_ms must be at least 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_settings_fail_ignition() {
+        let rocket = rocket::custom(Figment::from(rocket::Config::default())).attach(fairing());
+        match rocket::local::blocking::Client::tracked(rocket) {
+            Err(e) => assert!(matches!(
+                e.kind(),
+                rocket::error::ErrorKind::FailedFairings(_)
+            )),
+            Ok(_) => panic!("expected ignition to fail"),
+        }
+    }
+}
@@ -0,0 +1,219 @@
+//! Cross-origin policy, read from [`AppConfig::cors`].
+
+use crate::config::AppConfig;
+use rocket::fairing::AdHoc;
+use rocket_cors::{AllowedHeaders, AllowedMethods, AllowedOrigins, CorsOptions};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorsSettings {
+    /// Origins allowed verbatim, e.g. `https://app.example.com`.
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+    /// Origin patterns for preview deployments. Patterns are not anchored
+    /// implicitly, so include `^` and `$`.
+    #[serde(default)]
+    pub allowed_origin_regexes: Vec<String>,
+    #[serde(default = "default_allowed_headers")]
+    pub allowed_headers: Vec<String>,
+    /// Response headers browsers may read, such as rate-limit headers.
+    #[serde(default)]
+    pub expose_headers: Vec<String>,
+    #[serde(default)]
+    pub allow_credentials: bool,
+    /// Seconds browsers may cache a preflight response.
+    #[serde(default)]
+    pub max_age: Option<usize>,
+}
+
+impl Default for CorsSettings {
+    /// The policy of an empty `cors` table: no cross-origin access.
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            allowed_origin_regexes: Vec::new(),
+            allowed_headers: default_allowed_headers(),
+            expose_headers: Vec::new(),
+            allow_credentials: false,
+            max_age: None,
+        }
+    }
+}
+
+fn default_allowed_headers() -> Vec<String> {
+    ["Content-Type", "Authorization", "X-API-Key", "X-Request-Id"]
+        .map(String::from)
+        .to_vec()
+}
+
+impl CorsSettings {
+    fn options(&self) -> CorsOptions {
+        let allowed_methods: AllowedMethods = ["Get", "Post", "Options"]
+            .iter()
+            .map(|s| FromStr::from_str(s).unwrap())
+            .collect();
+        let allowed_headers: Vec<&str> = self.allowed_headers.iter().map(String::as_str).collect();
+
+        CorsOptions {
+            allowed_origins: AllowedOrigins::some(
+                &self.allowed_origins,
+                &self.allowed_origin_regexes,
+            ),
+            allowed_methods,
+            allowed_headers: AllowedHeaders::some(&allowed_headers),
+            expose_headers: self.expose_headers.iter().cloned().collect(),
+            allow_credentials: self.allow_credentials,
+            max_age: self.max_age,
+            ..Default::default()
+        }
+    }
+}
+
+pub fn fairing() -> AdHoc {
+    AdHoc::try_on_ignite("CORS", |rocket| async move {
+        let Some(config) = rocket.state::<AppConfig>() else {
+            error!("CORS needs the configuration fairing");
+            return Err(rocket);
+        };
+        match config.cors.options().to_cors() {
+            Ok(cors) => Ok(rocket.attach(cors)),
+            Err(e) => {
+                error!("invalid CORS settings: {}", e);
+                Err(rocket)
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source::memory;
+    use rocket::http::{Header, Method, Status};
+    use rocket::local::blocking::Client;
+
+    #[get("/ping")]
+    fn ping() -> &'static str {
+        "pong"
+    }
+
+    fn client(cors: CorsSettings) -> Client {
+        let config = AppConfig {
+            cors,
+            ..memory::config()
+        };
+        let rocket = rocket::build()
+            .mount("/", rocket::routes![ping])
+            .manage(config)
+            .attach(fairing());
+        Client::tracked(rocket).expect("valid rocket instance")
+    }
+
+    fn policy() -> CorsSettings {
+        CorsSettings {
+            allowed_origins: vec!["https://app.example.com".into()],
+            allowed_origin_regexes: vec![r"^https://[a-z0-9-]+\.preview\.example\.com$".into()],
+            expose_headers: vec!["X-RateLimit-Remaining".into(), "X-Request-Id".into()],
+            allow_credentials: true,
+            max_age: Some(600),
+            ..CorsSettings::default()
+        }
+    }
+
+    fn preflight<'c>(
+        client: &'c Client,
+        origin: &str,
+        headers: &str,
+    ) -> rocket::local::blocking::LocalResponse<'c> {
+        client
+            .req(Method::Options, "/ping")
+            .header(Header::new("Origin", origin.to_string()))
+            .header(Header::new("Access-Control-Request-Method", "GET"))
+            .header(Header::new(
+                "Access-Control-Request-Headers",
+                headers.to_string(),
+            ))
+            .dispatch()
+    }
+
+    #[test]
+    fn test_preflight_from_listed_origin() {
+        let client = client(policy());
+        let response = preflight(&client, "https://app.example.com", "X-API-Key");
+        assert_eq!(response.status(), Status::NoContent);
+        let headers = response.headers();
+        assert_eq!(
+            headers.get_one("Access-Control-Allow-Origin"),
+            Some("https://app.example.com")
+        );
+        assert_eq!(
+            headers.get_one("Access-Control-Allow-Credentials"),
+            Some("true")
+        );
+        assert_eq!(headers.get_one("Access-Control-Max-Age"), Some("600"));
+    }
+
+    #[test]
+    fn test_preflight_from_preview_origin() {
+        let client = client(policy());
+        let response = preflight(&client, "https://pr-42.preview.example.com", "Content-Type");
+        assert_eq!(response.status(), Status::NoContent);
+        let response = preflight(
+            &client,
+            "https://pr-42.preview.example.com.evil.io",
+            "Content-Type",
+        );
+        assert_eq!(response.status(), Status::Forbidden);
+    }
+
+    #[test]
+    fn test_preflight_rejects_unlisted_origin_and_header() {
+        let client = client(policy());
+        let response = preflight(&client, "https://evil.example.org", "Content-Type");
+        assert_eq!(response.status(), Status::Forbidden);
+        assert!(response
+            .headers()
+            .get_one("Access-Control-Allow-Origin")
+            .is_none());
+        let response = preflight(&client, "https://app.example.com", "X-Custom");
+        assert_eq!(response.status(), Status::Forbidden);
+    }
+
+    #[test]
+    fn test_actual_request_exposes_headers() {
+        let client = client(policy());
+        let response = client
+            .get("/ping")
+            .header(Header::new("Origin", "https://app.example.com"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let exposed = response
+            .headers()
+            .get_one("Access-Control-Expose-Headers")
+            .unwrap()
+            .to_string();
+        assert!(exposed.contains("X-RateLimit-Remaining"));
+        assert!(exposed.contains("X-Request-Id"));
+    }
+
+    #[test]
+    fn test_invalid_regex_fails_ignition() {
+        let rocket = rocket::build()
+            .manage(AppConfig {
+                cors: CorsSettings {
+                    allowed_origin_regexes: vec!["(".into()],
+                    ..CorsSettings::default()
+                },
+                ..memory::config()
+            })
+            .attach(fairing());
+        match Client::tracked(rocket) {
+            Err(e) => assert!(matches!(
+                e.kind(),
+                rocket::error::ErrorKind::FailedFairings(_)
+            )),
+            Ok(_) => panic!("expected ignition to fail"),
+        }
+    }
+}
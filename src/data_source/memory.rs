@@ -0,0 +1,483 @@
+//! In-memory data source and fixtures for exercising routes offline.
+
+use super::{
+    ChainTransaction, DataSourceError, OrderQuote, OrderRecord, OrderbookDataSource, Page,
+    PageRequest, TakeOrdersLimits, TimeRange, TradeLeg, TradeRecord, TransactionOrders,
+    TransactionRecord, TransactionTrades, VaultRecord,
+};
+use crate::auth::{ApiKeyEntry, ApiKeyStore, Scope, DEFAULT_TIER};
+use crate::config::AppConfig;
+use crate::cors::CorsSettings;
+use crate::health::{HealthSettings, ReadinessCache};
+use crate::metrics::{Metrics, MetricsSettings};
+use crate::orderbook::{EvaluableV3, OrderV3, IO};
+use crate::strategy::Deployment;
+use crate::token_registry::{TokenEntry, TokenRegistry};
+use crate::types::common::TokenRef;
+use crate::types::order::OrderType;
+use alloy::primitives::{Address, Bytes, B256, U256};
+use alloy::sol_types::SolValue;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+#[derive(Default)]
+pub struct InMemoryDataSource {
+    orders: Vec<OrderRecord>,
+    order_txs: HashMap<B256, Vec<B256>>,
+    trades: Vec<TradeRecord>,
+    transactions: HashMap<B256, TransactionRecord>,
+    limits: HashMap<B256, TakeOrdersLimits>,
+    quotes: HashMap<B256, OrderQuote>,
+    failing_quotes: HashSet<B256>,
+    chain_txs: HashMap<B256, ChainTransaction>,
+    indexed_block: u64,
+    chain_head: u64,
+    rpc_down: bool,
+    subgraph_down: bool,
+}
+
+impl InMemoryDataSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_order(mut self, order: OrderRecord) -> Self {
+        self.orders.push(order);
+        self
+    }
+
+    /// Adds an order along with the transaction that added it.
+    pub fn with_order_added_in(mut self, order: OrderRecord, tx: TransactionRecord) -> Self {
+        self.order_txs
+            .entry(tx.tx_hash)
+            .or_default()
+            .push(order.order_hash);
+        self.transactions.insert(tx.tx_hash, tx);
+        self.with_order(order)
+    }
+
+    pub fn with_trade(mut self, trade: TradeRecord) -> Self {
+        self.transactions
+            .insert(trade.transaction.tx_hash, trade.transaction.clone());
+        self.trades.push(trade);
+        self
+    }
+
+    pub fn with_limits(mut self, tx_hash: B256, limits: TakeOrdersLimits) -> Self {
+        self.limits.insert(tx_hash, limits);
+        self
+    }
+
+    pub fn with_quote(mut self, order_hash: B256, quote: OrderQuote) -> Self {
+        self.quotes.insert(order_hash, quote);
+        self
+    }
+
+    /// Fails the quote for one order while the rest of the RPC stays up.
+    pub fn with_quote_error(mut self, order_hash: B256) -> Self {
+        self.failing_quotes.insert(order_hash);
+        self
+    }
+
+    /// Records a transaction the chain knows about, whether or not it is indexed.
+    pub fn with_chain_tx(mut self, tx_hash: B256, tx: ChainTransaction) -> Self {
+        self.chain_txs.insert(tx_hash, tx);
+        self
+    }
+
+    pub fn with_indexed_block(mut self, block_number: u64) -> Self {
+        self.indexed_block = block_number;
+        self
+    }
+
+    pub fn with_chain_head(mut self, block_number: u64) -> Self {
+        self.chain_head = block_number;
+        self
+    }
+
+    /// Fails every call that would reach the RPC node.
+    pub fn with_rpc_down(mut self) -> Self {
+        self.rpc_down = true;
+        self
+    }
+
+    /// Fails every call that would reach the subgraph.
+    pub fn with_subgraph_down(mut self) -> Self {
+        self.subgraph_down = true;
+        self
+    }
+
+    fn paginate<T: Clone>(items: Vec<T>, page: PageRequest) -> Page<T> {
+        let total = items.len() as u64;
+        let items = items
+            .into_iter()
+            .skip(page.skip() as usize)
+            .take(page.page_size as usize)
+            .collect();
+        Page { items, total }
+    }
+}
+
+fn in_range(range: TimeRange, timestamp: u64) -> bool {
+    range.start.is_none_or(|start| timestamp >= start)
+        && range.end.is_none_or(|end| timestamp < end)
+}
+
+#[rocket::async_trait]
+impl OrderbookDataSource for InMemoryDataSource {
+    async fn order_by_hash(
+        &self,
+        order_hash: B256,
+    ) -> Result<Option<OrderRecord>, DataSourceError> {
+        Ok(self
+            .orders
+            .iter()
+            .find(|order| order.order_hash == order_hash)
+            .cloned())
+    }
+
+    async fn trades_by_order(&self, order_hash: B256) -> Result<Vec<TradeRecord>, DataSourceError> {
+        let mut trades: Vec<_> = self
+            .trades
+            .iter()
+            .filter(|trade| trade.order_hash == order_hash)
+            .cloned()
+            .collect();
+        trades.sort_by_key(|trade| Reverse(trade.transaction.timestamp));
+        Ok(trades)
+    }
+
+    async fn orders_by_owner(
+        &self,
+        owner: Address,
+        page: PageRequest,
+    ) -> Result<Page<OrderRecord>, DataSourceError> {
+        let mut orders: Vec<_> = self
+            .orders
+            .iter()
+            .filter(|order| order.owner == owner)
+            .cloned()
+            .collect();
+        orders.sort_by_key(|order| Reverse(order.timestamp_added));
+        Ok(Self::paginate(orders, page))
+    }
+
+    async fn orders_by_tx(
+        &self,
+        tx_hash: B256,
+    ) -> Result<Option<TransactionOrders>, DataSourceError> {
+        let Some(transaction) = self.transactions.get(&tx_hash) else {
+            return Ok(None);
+        };
+        let hashes = self.order_txs.get(&tx_hash).cloned().unwrap_or_default();
+        Ok(Some(TransactionOrders {
+            transaction: transaction.clone(),
+            orders: self
+                .orders
+                .iter()
+                .filter(|order| hashes.contains(&order.order_hash))
+                .cloned()
+                .collect(),
+        }))
+    }
+
+    async fn orders_by_pair(
+        &self,
+        input_token: Address,
+        output_token: Address,
+    ) -> Result<Vec<OrderRecord>, DataSourceError> {
+        Ok(self
+            .orders
+            .iter()
+            .filter(|order| {
+                order.active
+                    && order.input.token.address == input_token
+                    && order.output.token.address == output_token
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn active_orders_using_vault(
+        &self,
+        owner: Address,
+        token: Address,
+        vault_id: U256,
+    ) -> Result<Vec<OrderRecord>, DataSourceError> {
+        let uses_vault =
+            |vault: &VaultRecord| vault.token.address == token && vault.vault_id == vault_id;
+        Ok(self
+            .orders
+            .iter()
+            .filter(|order| {
+                order.active
+                    && order.owner == owner
+                    && (uses_vault(&order.input) || uses_vault(&order.output))
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn trades_by_tx(
+        &self,
+        tx_hash: B256,
+    ) -> Result<Option<TransactionTrades>, DataSourceError> {
+        let Some(transaction) = self.transactions.get(&tx_hash) else {
+            return Ok(None);
+        };
+        Ok(Some(TransactionTrades {
+            transaction: transaction.clone(),
+            limits: self.limits.get(&tx_hash).cloned(),
+            trades: self
+                .trades
+                .iter()
+                .filter(|trade| trade.transaction.tx_hash == tx_hash)
+                .cloned()
+                .collect(),
+        }))
+    }
+
+    async fn trades_by_owner(
+        &self,
+        owner: Address,
+        range: TimeRange,
+        page: PageRequest,
+    ) -> Result<Page<TradeRecord>, DataSourceError> {
+        let mut trades: Vec<_> = self
+            .trades
+            .iter()
+            .filter(|trade| {
+                trade.order_owner == owner && in_range(range, trade.transaction.timestamp)
+            })
+            .cloned()
+            .collect();
+        trades.sort_by_key(|trade| Reverse(trade.transaction.timestamp));
+        Ok(Self::paginate(trades, page))
+    }
+
+    async fn chain_transaction(&self, tx_hash: B256) -> Result<ChainTransaction, DataSourceError> {
+        if self.rpc_down {
+            return Err(DataSourceError::Request("rpc unreachable".into()));
+        }
+        Ok(self
+            .chain_txs
+            .get(&tx_hash)
+            .copied()
+            .unwrap_or(ChainTransaction::Unknown))
+    }
+
+    async fn indexed_block(&self) -> Result<u64, DataSourceError> {
+        if self.subgraph_down {
+            return Err(DataSourceError::Request("subgraph unreachable".into()));
+        }
+        Ok(self.indexed_block)
+    }
+
+    async fn chain_head(&self) -> Result<u64, DataSourceError> {
+        if self.rpc_down {
+            return Err(DataSourceError::Request("rpc unreachable".into()));
+        }
+        Ok(self.chain_head)
+    }
+
+    async fn quote(&self, order: &OrderRecord) -> Result<Option<OrderQuote>, DataSourceError> {
+        if self.rpc_down || self.failing_quotes.contains(&order.order_hash) {
+            return Err(DataSourceError::Request("rpc unreachable".into()));
+        }
+        Ok(self.quotes.get(&order.order_hash).cloned())
+    }
+
+    /// Passes the source through unchanged so tests can read it back out of
+    /// the deployed bytecode.
+    async fn parse_rainlang(
+        &self,
+        _deployer: Address,
+        rainlang: &str,
+    ) -> Result<Bytes, DataSourceError> {
+        Ok(Bytes::copy_from_slice(rainlang.as_bytes()))
+    }
+}
+
+pub const ORDERBOOK: Address = Address::repeat_byte(0x0b);
+pub const OWNER: Address = Address::repeat_byte(0x01);
+pub const TAKER: Address = Address::repeat_byte(0x02);
+
+pub fn usdc() -> TokenRef {
+    TokenRef {
+        address: Address::repeat_byte(0xaa),
+        symbol: "USDC".into(),
+        decimals: 6,
+    }
+}
+
+pub fn weth() -> TokenRef {
+    TokenRef {
+        address: Address::repeat_byte(0xbb),
+        symbol: "WETH".into(),
+        decimals: 18,
+    }
+}
+
+/// A registry holding [`usdc`] and [`weth`].
+pub fn registry() -> TokenRegistry {
+    let entry = |token: TokenRef, isin: &str| TokenEntry {
+        chain_id: 8453,
+        address: token.address,
+        name: token.symbol.clone(),
+        symbol: token.symbol,
+        isin: isin.into(),
+        decimals: token.decimals,
+    };
+    TokenRegistry::from_entries(
+        8453,
+        vec![entry(usdc(), "US0378331005"), entry(weth(), "US5949181045")],
+    )
+    .expect("valid registry")
+}
+
+pub fn deployment() -> Deployment {
+    Deployment {
+        orderbook: ORDERBOOK,
+        deployer: Address::repeat_byte(0x0a),
+        interpreter: Address::repeat_byte(0x0c),
+        store: Address::repeat_byte(0x0d),
+        sub_parser: Address::repeat_byte(0x0e),
+    }
+}
+
+pub fn tx(byte: u8, timestamp: u64) -> TransactionRecord {
+    TransactionRecord {
+        tx_hash: B256::repeat_byte(byte),
+        block_number: 1000 + u64::from(byte),
+        timestamp,
+        sender: TAKER,
+    }
+}
+
+/// An order selling WETH for USDC, with its `OrderV3` encoded as the
+/// subgraph would return it.
+pub fn order(byte: u8, owner: Address, timestamp_added: u64) -> OrderRecord {
+    let input = VaultRecord {
+        vault_id: U256::from(1),
+        token: usdc(),
+        balance: U256::from(2_000_000),
+    };
+    let output = VaultRecord {
+        vault_id: U256::from(2),
+        token: weth(),
+        balance: U256::from(1_000_000_000_000_000_000u128),
+    };
+    let order = OrderV3 {
+        owner,
+        evaluable: EvaluableV3 {
+            interpreter: Address::repeat_byte(0x0c),
+            store: Address::repeat_byte(0x0d),
+            bytecode: Bytes::new(),
+        },
+        validInputs: vec![IO {
+            token: input.token.address,
+            decimals: input.token.decimals,
+            vaultId: input.vault_id,
+        }],
+        validOutputs: vec![IO {
+            token: output.token.address,
+            decimals: output.token.decimals,
+            vaultId: output.vault_id,
+        }],
+        nonce: B256::repeat_byte(byte),
+    };
+    OrderRecord {
+        order_hash: B256::repeat_byte(byte),
+        owner,
+        orderbook: ORDERBOOK,
+        order_bytes: order.abi_encode().into(),
+        input,
+        output,
+        order_type: OrderType::Solver,
+        active: true,
+        timestamp_added,
+    }
+}
+
+/// A trade against `order` where the order received `input` USDC and gave
+/// `output` WETH.
+pub fn trade(order: &OrderRecord, tx: TransactionRecord, input: u128, output: u128) -> TradeRecord {
+    TradeRecord {
+        id: format!("{}-{}", tx.tx_hash, order.order_hash),
+        order_hash: order.order_hash,
+        order_owner: order.owner,
+        sender: tx.sender,
+        transaction: tx,
+        input: TradeLeg {
+            token: order.input.token.clone(),
+            amount: U256::from(input),
+        },
+        output: TradeLeg {
+            token: order.output.token.clone(),
+            amount: U256::from(output),
+        },
+    }
+}
+
+/// Plaintext of the test key, which holds every scope.
+pub const API_KEY: &str = "test-api-key";
+/// Plaintext of a partner key that may read and quote but not deploy.
+pub const PARTNER_API_KEY: &str = "partner-api-key";
+
+fn api_key_entries() -> Vec<ApiKeyEntry> {
+    vec![
+        ApiKeyEntry {
+            name: "test".into(),
+            key_hash: ApiKeyStore::hash_key(API_KEY),
+            scopes: vec![Scope::Read, Scope::Quote, Scope::Deploy, Scope::Admin],
+            tier: DEFAULT_TIER.into(),
+        },
+        ApiKeyEntry {
+            name: "partner".into(),
+            key_hash: ApiKeyStore::hash_key(PARTNER_API_KEY),
+            scopes: vec![Scope::Read, Scope::Quote],
+            tier: DEFAULT_TIER.into(),
+        },
+    ]
+}
+
+pub fn api_keys() -> ApiKeyStore {
+    ApiKeyStore::new(api_key_entries()).expect("unique test keys")
+}
+
+/// Configuration matching the other fixtures. No route is rate limited.
+pub fn config() -> AppConfig {
+    AppConfig {
+        chain_id: 8453,
+        token_registry: "tokens.toml".into(),
+        subgraph_url: "http://127.0.0.1:8000/subgraphs/name/rain-orderbook".into(),
+        rpc_url: "http://127.0.0.1:8545".into(),
+        deployment: deployment(),
+        api_keys: api_key_entries(),
+        rate_limits: HashMap::from([(DEFAULT_TIER.to_string(), HashMap::new())]),
+        cors: CorsSettings::default(),
+        health: HealthSettings::default(),
+        metrics: MetricsSettings::default(),
+    }
+}
+
+/// A client with `routes` mounted at `base`, backed by `source` and the
+/// [`registry`], [`api_keys`] and [`config`] fixtures.
+pub fn client(
+    base: &str,
+    routes: Vec<rocket::Route>,
+    source: InMemoryDataSource,
+) -> rocket::local::blocking::Client {
+    let source: Box<dyn OrderbookDataSource> = Box::new(source);
+    let rocket = rocket::build()
+        .mount(base, routes)
+        .register("/", crate::error::catchers())
+        .manage(source)
+        .manage(registry())
+        .manage(api_keys())
+        .manage(config())
+        .manage(ReadinessCache::default())
+        .manage(Arc::new(Metrics::default()));
+    rocket::local::blocking::Client::tracked(rocket).expect("valid rocket instance")
+}
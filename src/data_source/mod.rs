@@ -0,0 +1,396 @@
+//! Where order and trade data comes from.
+//!
+//! Handlers only see [`OrderbookDataSource`], held in Rocket state as a
+//! boxed trait object, so the HTTP surface can run against the subgraph in
+//! production and against [`memory::InMemoryDataSource`] in tests.
+
+#[cfg(test)]
+pub mod memory;
+pub mod subgraph;
+
+use crate::config::AppConfig;
+use crate::error::ApiError;
+use crate::metrics::Metrics;
+use crate::types::common::{Ratio, TokenRef};
+use crate::types::order::OrderType;
+use alloy::primitives::{Address, Bytes, B256, U256};
+use futures::stream::{self, StreamExt};
+use rocket::fairing::AdHoc;
+use std::sync::Arc;
+
+#[derive(Debug, thiserror::Error)]
+pub enum DataSourceError {
+    #[error("upstream request failed: {0}")]
+    Request(String),
+    #[error("invalid upstream response: {0}")]
+    InvalidResponse(String),
+}
+
+impl DataSourceError {
+    /// A summary safe to show callers. The detail can hold endpoint URLs,
+    /// which routinely carry provider API keys, so it is only logged.
+    pub fn public_message(&self) -> &'static str {
+        match self {
+            DataSourceError::Request(_) => "upstream data source unavailable",
+            DataSourceError::InvalidResponse(_) => "invalid upstream response",
+        }
+    }
+}
+
+impl From<DataSourceError> for ApiError {
+    fn from(e: DataSourceError) -> Self {
+        warn!("data source error: {}", e);
+        let message = e.public_message().to_string();
+        match e {
+            DataSourceError::Request(_) => ApiError::ServiceUnavailable(message),
+            DataSourceError::InvalidResponse(_) => ApiError::Internal(message),
+        }
+    }
+}
+
+/// A vault referenced by an order's first input or output IO.
+#[derive(Debug, Clone)]
+pub struct VaultRecord {
+    pub vault_id: U256,
+    pub token: TokenRef,
+    pub balance: U256,
+}
+
+#[derive(Debug, Clone)]
+pub struct OrderRecord {
+    pub order_hash: B256,
+    pub owner: Address,
+    pub orderbook: Address,
+    /// ABI-encoded `OrderV3`, needed to quote, take or remove the order.
+    pub order_bytes: Bytes,
+    pub input: VaultRecord,
+    pub output: VaultRecord,
+    pub order_type: OrderType,
+    /// False once the order has been removed from the orderbook.
+    pub active: bool,
+    pub timestamp_added: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TransactionRecord {
+    pub tx_hash: B256,
+    pub block_number: u64,
+    pub timestamp: u64,
+    pub sender: Address,
+}
+
+/// One side of a trade, from the order's point of view.
+#[derive(Debug, Clone)]
+pub struct TradeLeg {
+    pub token: TokenRef,
+    pub amount: U256,
+}
+
+#[derive(Debug, Clone)]
+pub struct TradeRecord {
+    pub id: String,
+    pub transaction: TransactionRecord,
+    pub order_hash: B256,
+    pub order_owner: Address,
+    /// The account that took the order, which may differ from the
+    /// transaction sender when routed through a contract.
+    pub sender: Address,
+    pub input: TradeLeg,
+    pub output: TradeLeg,
+}
+
+/// The limits the taker submitted with `takeOrders2`.
+#[derive(Debug, Clone)]
+pub struct TakeOrdersLimits {
+    pub maximum_input: U256,
+    pub maximum_io_ratio: U256,
+}
+
+#[derive(Debug, Clone)]
+pub struct TransactionOrders {
+    pub transaction: TransactionRecord,
+    pub orders: Vec<OrderRecord>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TransactionTrades {
+    pub transaction: TransactionRecord,
+    pub limits: Option<TakeOrdersLimits>,
+    pub trades: Vec<TradeRecord>,
+}
+
+/// What the chain itself knows about a transaction, independent of the index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainTransaction {
+    Unknown,
+    Pending,
+    Mined { block_number: u64 },
+}
+
+/// Current on-chain quote for an order.
+#[derive(Debug, Clone)]
+pub struct OrderQuote {
+    /// 18-decimal fixed point input per unit of output.
+    pub io_ratio: U256,
+    /// Most the order can currently give, 18-decimal fixed point.
+    pub max_output: U256,
+}
+
+impl OrderQuote {
+    /// An optional quote's ratio, reporting `0` for orders that cannot
+    /// currently be quoted.
+    pub fn io_ratio_or_zero(quote: Option<&OrderQuote>) -> Ratio {
+        Ratio(quote.map_or(U256::ZERO, |quote| quote.io_ratio))
+    }
+}
+
+pub const DEFAULT_PAGE_SIZE: u32 = 20;
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+/// 1-based page request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageRequest {
+    pub page: u32,
+    pub page_size: u32,
+}
+
+impl PageRequest {
+    /// Applies defaults to query parameters and rejects values the backend
+    /// should never see.
+    pub fn from_params(page: Option<u32>, page_size: Option<u32>) -> Result<Self, ApiError> {
+        let page = page.unwrap_or(1);
+        let page_size = page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+        if page == 0 {
+            return Err(ApiError::BadRequest("page must be at least 1".into()));
+        }
+        if page_size == 0 || page_size > MAX_PAGE_SIZE {
+            return Err(ApiError::BadRequest(format!(
+                "pageSize must be between 1 and {MAX_PAGE_SIZE}"
+            )));
+        }
+        Ok(Self { page, page_size })
+    }
+
+    pub fn skip(&self) -> u64 {
+        u64::from(self.page.saturating_sub(1)) * u64::from(self.page_size)
+    }
+}
+
+/// Unix-second window, inclusive of `start` and exclusive of `end`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeRange {
+    pub start: Option<u64>,
+    pub end: Option<u64>,
+}
+
+impl TimeRange {
+    pub fn new(start: Option<u64>, end: Option<u64>) -> Result<Self, ApiError> {
+        if let (Some(start), Some(end)) = (start, end) {
+            if start > end {
+                return Err(ApiError::BadRequest(
+                    "startTime must not be after endTime".into(),
+                ));
+            }
+        }
+        Ok(Self { start, end })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: u64,
+}
+
+impl<T> Page<T> {
+    pub fn total_pages(&self, page_size: u32) -> u64 {
+        self.total.div_ceil(u64::from(page_size.max(1)))
+    }
+}
+
+#[rocket::async_trait]
+pub trait OrderbookDataSource: Send + Sync {
+    async fn order_by_hash(&self, order_hash: B256)
+        -> Result<Option<OrderRecord>, DataSourceError>;
+
+    /// Every trade against an order, newest first.
+    async fn trades_by_order(&self, order_hash: B256) -> Result<Vec<TradeRecord>, DataSourceError>;
+
+    /// An owner's orders, newest first.
+    async fn orders_by_owner(
+        &self,
+        owner: Address,
+        page: PageRequest,
+    ) -> Result<Page<OrderRecord>, DataSourceError>;
+
+    async fn orders_by_tx(
+        &self,
+        tx_hash: B256,
+    ) -> Result<Option<TransactionOrders>, DataSourceError>;
+
+    /// Every active order whose first IOs take `input_token` and give
+    /// `output_token`.
+    async fn orders_by_pair(
+        &self,
+        input_token: Address,
+        output_token: Address,
+    ) -> Result<Vec<OrderRecord>, DataSourceError>;
+
+    /// An owner's active orders that read from or write to a vault, which is
+    /// identified by owner, token and vault ID.
+    async fn active_orders_using_vault(
+        &self,
+        owner: Address,
+        token: Address,
+        vault_id: U256,
+    ) -> Result<Vec<OrderRecord>, DataSourceError>;
+
+    async fn trades_by_tx(
+        &self,
+        tx_hash: B256,
+    ) -> Result<Option<TransactionTrades>, DataSourceError>;
+
+    /// Trades against an owner's orders within `range`, newest first.
+    async fn trades_by_owner(
+        &self,
+        owner: Address,
+        range: TimeRange,
+        page: PageRequest,
+    ) -> Result<Page<TradeRecord>, DataSourceError>;
+
+    async fn chain_transaction(&self, tx_hash: B256) -> Result<ChainTransaction, DataSourceError>;
+
+    /// Latest block the index has processed.
+    async fn indexed_block(&self) -> Result<u64, DataSourceError>;
+
+    /// Latest block of the chain.
+    async fn chain_head(&self) -> Result<u64, DataSourceError>;
+
+    /// `None` when the order does not exist on-chain or cannot be evaluated.
+    async fn quote(&self, order: &OrderRecord) -> Result<Option<OrderQuote>, DataSourceError>;
+
+    /// Compiles Rainlang into bytecode with `deployer`'s parser.
+    async fn parse_rainlang(
+        &self,
+        deployer: Address,
+        rainlang: &str,
+    ) -> Result<Bytes, DataSourceError>;
+}
+
+/// Explains why an indexed lookup for `tx_hash` came back empty: the index
+/// may simply not have caught up with the chain yet.
+pub async fn missing_transaction_error(
+    data_source: &dyn OrderbookDataSource,
+    tx_hash: B256,
+) -> ApiError {
+    let result = match data_source.chain_transaction(tx_hash).await {
+        Ok(ChainTransaction::Unknown) => Ok(false),
+        Ok(ChainTransaction::Pending) => Ok(true),
+        Ok(ChainTransaction::Mined { block_number }) => data_source
+            .indexed_block()
+            .await
+            .map(|indexed| block_number > indexed),
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(true) => ApiError::NotYetIndexed(format!("transaction {tx_hash} is not yet indexed")),
+        Ok(false) => ApiError::NotFound(format!("transaction {tx_hash} not found")),
+        Err(e) => e.into(),
+    }
+}
+
+/// Most quotes a single request keeps in flight against the RPC node.
+const QUOTE_CONCURRENCY: usize = 8;
+
+/// Quotes `orders` a few at a time, keeping them in order. Each order keeps
+/// its own result, so one failing quote does not sink the others.
+pub async fn quote_orders(
+    data_source: &dyn OrderbookDataSource,
+    orders: Vec<OrderRecord>,
+) -> Vec<(OrderRecord, Result<Option<OrderQuote>, DataSourceError>)> {
+    stream::iter(orders)
+        .map(|order| async move {
+            let quote = data_source.quote(&order).await;
+            (order, quote)
+        })
+        .buffered(QUOTE_CONCURRENCY)
+        .collect()
+        .await
+}
+
+pub fn fairing() -> AdHoc {
+    AdHoc::try_on_ignite("Orderbook data source", |rocket| async move {
+        let (Some(config), Some(metrics)) =
+            (rocket.state::<AppConfig>(), rocket.state::<Arc<Metrics>>())
+        else {
+            error!("data source needs the configuration and metrics fairings");
+            return Err(rocket);
+        };
+        let source: Box<dyn OrderbookDataSource> = Box::new(subgraph::SubgraphDataSource::new(
+            config.subgraph_url.clone(),
+            config.rpc_url.clone(),
+            metrics.clone(),
+        ));
+        Ok(rocket.manage(source))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_errors_hide_upstream_detail() {
+        let detail = "error sending request for url (https://rpc.example.com/v2/secret-key)";
+        let error = ApiError::from(DataSourceError::Request(detail.into()));
+        assert!(
+            matches!(&error, ApiError::ServiceUnavailable(msg) if msg == "upstream data source unavailable")
+        );
+        let error = ApiError::from(DataSourceError::InvalidResponse(detail.into()));
+        assert!(matches!(&error, ApiError::Internal(msg) if msg == "invalid upstream response"));
+    }
+
+    #[test]
+    fn test_page_request_defaults() {
+        let page = PageRequest::from_params(None, None).unwrap();
+        assert_eq!(page.page, 1);
+        assert_eq!(page.page_size, DEFAULT_PAGE_SIZE);
+        assert_eq!(page.skip(), 0);
+    }
+
+    #[test]
+    fn test_page_request_skip() {
+        let page = PageRequest::from_params(Some(3), Some(25)).unwrap();
+        assert_eq!(page.skip(), 50);
+    }
+
+    #[test]
+    fn test_page_request_rejects_out_of_range() {
+        assert!(PageRequest::from_params(Some(0), None).is_err());
+        assert!(PageRequest::from_params(None, Some(0)).is_err());
+        assert!(PageRequest::from_params(None, Some(MAX_PAGE_SIZE + 1)).is_err());
+        assert!(PageRequest::from_params(None, Some(1_000_000)).is_err());
+        assert!(PageRequest::from_params(None, Some(MAX_PAGE_SIZE)).is_ok());
+    }
+
+    #[test]
+    fn test_time_range() {
+        assert!(TimeRange::new(None, None).is_ok());
+        assert!(TimeRange::new(Some(100), None).is_ok());
+        assert!(TimeRange::new(None, Some(100)).is_ok());
+        assert!(TimeRange::new(Some(100), Some(100)).is_ok());
+        assert!(TimeRange::new(Some(201), Some(200)).is_err());
+    }
+
+    #[test]
+    fn test_total_pages() {
+        let page = |total| Page::<()> {
+            items: vec![],
+            total,
+        };
+        assert_eq!(page(0).total_pages(20), 0);
+        assert_eq!(page(1).total_pages(20), 1);
+        assert_eq!(page(20).total_pages(20), 1);
+        assert_eq!(page(21).total_pages(20), 2);
+    }
+}
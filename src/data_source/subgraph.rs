@@ -0,0 +1,925 @@
+//! Production data source: indexed data from the orderbook subgraph, live
+//! quotes and transaction calldata from a JSON-RPC node.
+
+use super::{
+    ChainTransaction, DataSourceError, OrderQuote, OrderRecord, OrderbookDataSource, Page,
+    PageRequest, TakeOrdersLimits, TimeRange, TradeLeg, TradeRecord, TransactionOrders,
+    TransactionRecord, TransactionTrades, VaultRecord,
+};
+use crate::cache::TtlCache;
+use crate::metrics::Metrics;
+use crate::orderbook::{IOrderBookV4, IParserV2, OrderV3, Quote};
+use crate::strategy;
+use crate::types::common::TokenRef;
+use alloy::primitives::{Address, Bytes, B256, U256, U64};
+use alloy::sol_types::{SolCall, SolValue};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::cmp::Reverse;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Largest `first` the graph-node accepts.
+const MAX_SUBGRAPH_PAGE: u64 = 1000;
+/// How long an owner's total stays cached, so paging through a listing
+/// counts once rather than on every page.
+const COUNT_TTL: Duration = Duration::from_secs(30);
+/// Most owner totals cached at once.
+const MAX_CACHED_COUNTS: usize = 10_000;
+
+const ORDER_FIELDS: &str = r#"
+fragment OrderFields on Order {
+  orderHash
+  owner
+  orderBytes
+  meta
+  active
+  timestampAdded
+  orderbook { id }
+  inputs { vaultId balance token { address symbol decimals } }
+  outputs { vaultId balance token { address symbol decimals } }
+}
+"#;
+
+const TRADE_FIELDS: &str = r#"
+fragment TradeFields on Trade {
+  id
+  order { orderHash owner }
+  tradeEvent { sender transaction { id blockNumber timestamp from } }
+  inputVaultBalanceChange { amount vault { token { address symbol decimals } } }
+  outputVaultBalanceChange { amount vault { token { address symbol decimals } } }
+}
+"#;
+
+const TRANSACTION_FIELDS: &str = "id blockNumber timestamp from";
+
+pub struct SubgraphDataSource {
+    client: reqwest::Client,
+    subgraph_url: String,
+    rpc_url: String,
+    metrics: Arc<Metrics>,
+    /// Totals from `count_by_owner`, keyed by entity, owner and filter.
+    counts: TtlCache<String, u64>,
+}
+
+impl SubgraphDataSource {
+    pub fn new(subgraph_url: String, rpc_url: String, metrics: Arc<Metrics>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            subgraph_url,
+            rpc_url,
+            metrics,
+            counts: TtlCache::new(COUNT_TTL, MAX_CACHED_COUNTS),
+        }
+    }
+
+    async fn query<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: Value,
+    ) -> Result<T, DataSourceError> {
+        self.metrics
+            .observe_upstream("subgraph", self.send_query(query, variables))
+            .await
+    }
+
+    async fn send_query<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: Value,
+    ) -> Result<T, DataSourceError> {
+        let response: GraphQlResponse<T> = self
+            .client
+            .post(&self.subgraph_url)
+            .json(&json!({ "query": query, "variables": variables }))
+            .send()
+            .await
+            .map_err(|e| DataSourceError::Request(e.to_string()))?
+            .error_for_status()
+            .map_err(|e| DataSourceError::Request(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| DataSourceError::InvalidResponse(e.to_string()))?;
+
+        if let Some(errors) = response.errors.filter(|errors| !errors.is_empty()) {
+            let messages: Vec<_> = errors.into_iter().map(|e| e.message).collect();
+            return Err(DataSourceError::Request(messages.join("; ")));
+        }
+        response
+            .data
+            .ok_or_else(|| DataSourceError::InvalidResponse("missing data".into()))
+    }
+
+    /// Counts an owner's entities matching `filter` by walking ids, since the
+    /// subgraph has no aggregate count. Totals are cached for `COUNT_TTL`.
+    async fn count_by_owner(
+        &self,
+        entity: &str,
+        filter: &str,
+        owner: Address,
+    ) -> Result<u64, DataSourceError> {
+        let key = format!("{entity}:{owner}:{filter}");
+        let cached = self.counts.get(&key);
+        self.metrics
+            .record_cache_lookup("owner_counts", cached.is_some());
+        if let Some(total) = cached {
+            return Ok(total);
+        }
+        let total = self.walk_count(entity, filter, owner).await?;
+        self.counts.insert(key, total);
+        Ok(total)
+    }
+
+    async fn walk_count(
+        &self,
+        entity: &str,
+        filter: &str,
+        owner: Address,
+    ) -> Result<u64, DataSourceError> {
+        let query = format!(
+            "query Count($owner: Bytes!, $lastId: String!) {{ \
+             items: {entity}(where: {{ {filter}, id_gt: $lastId }}, first: {MAX_SUBGRAPH_PAGE}, orderBy: id) {{ id }} }}"
+        );
+        let mut total = 0;
+        let mut last_id = String::new();
+        loop {
+            let variables = json!({ "owner": owner, "lastId": last_id });
+            let page: IdsData = self.query(&query, variables).await?;
+            total += page.items.len() as u64;
+            match page.items.last() {
+                Some(item) if page.items.len() as u64 == MAX_SUBGRAPH_PAGE => {
+                    last_id = item.id.clone();
+                }
+                _ => return Ok(total),
+            }
+        }
+    }
+
+    /// Fetches every row `query` matches by walking ids, rather than stopping
+    /// at the subgraph's page limit. `query` must select its rows, including
+    /// `id`, as `items`, filtered on `id_gt: $lastId` and ordered by `id`.
+    async fn all_by_id<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        mut variables: Value,
+    ) -> Result<Vec<T>, DataSourceError> {
+        let mut rows = Vec::new();
+        loop {
+            let page: RowsData = self.query(query, variables.clone()).await?;
+            let full = page.items.len() as u64 == MAX_SUBGRAPH_PAGE;
+            let last_id = page.items.last().map(|item| item["id"].clone());
+            for item in page.items {
+                rows.push(
+                    serde_json::from_value(item)
+                        .map_err(|e| DataSourceError::InvalidResponse(e.to_string()))?,
+                );
+            }
+            match last_id {
+                Some(Value::String(id)) if full => variables["lastId"] = Value::String(id),
+                Some(Value::String(_)) | None => return Ok(rows),
+                Some(_) => return Err(DataSourceError::InvalidResponse("missing id".into())),
+            }
+        }
+    }
+
+    async fn transaction(
+        &self,
+        tx_hash: B256,
+    ) -> Result<Option<TransactionRecord>, DataSourceError> {
+        let query = format!(
+            "query Transaction($id: ID!) {{ transaction(id: $id) {{ {TRANSACTION_FIELDS} }} }}"
+        );
+        let data: TransactionData = self.query(&query, json!({ "id": tx_hash })).await?;
+        data.transaction.map(TryInto::try_into).transpose()
+    }
+
+    async fn rpc<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<Option<T>, DataSourceError> {
+        self.metrics
+            .observe_upstream("rpc", self.send_rpc(method, params))
+            .await
+    }
+
+    async fn send_rpc<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<Option<T>, DataSourceError> {
+        let response: RpcResponse<T> = self
+            .client
+            .post(&self.rpc_url)
+            .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+            .send()
+            .await
+            .map_err(|e| DataSourceError::Request(e.to_string()))?
+            .error_for_status()
+            .map_err(|e| DataSourceError::Request(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| DataSourceError::InvalidResponse(e.to_string()))?;
+        rpc_result(method, response)
+    }
+
+    /// Recovers the taker's limits when the transaction called `takeOrders2`
+    /// directly; routed or batched calls are not decoded.
+    async fn take_orders_limits(
+        &self,
+        tx_hash: B256,
+    ) -> Result<Option<TakeOrdersLimits>, DataSourceError> {
+        let tx: Option<RpcTransaction> = self
+            .rpc("eth_getTransactionByHash", json!([tx_hash]))
+            .await?;
+        let Some(tx) = tx else {
+            return Ok(None);
+        };
+        Ok(IOrderBookV4::takeOrders2Call::abi_decode(&tx.input)
+            .ok()
+            .map(|call| TakeOrdersLimits {
+                maximum_input: call.config.maximumInput,
+                maximum_io_ratio: call.config.maximumIORatio,
+            }))
+    }
+}
+
+#[rocket::async_trait]
+impl OrderbookDataSource for SubgraphDataSource {
+    async fn order_by_hash(
+        &self,
+        order_hash: B256,
+    ) -> Result<Option<OrderRecord>, DataSourceError> {
+        let query = format!(
+            "query Order($hash: Bytes!) {{ orders(where: {{ orderHash: $hash }}, first: 1) {{ ...OrderFields }} }} {ORDER_FIELDS}"
+        );
+        let data: OrdersData = self.query(&query, json!({ "hash": order_hash })).await?;
+        data.orders
+            .into_iter()
+            .next()
+            .map(TryInto::try_into)
+            .transpose()
+    }
+
+    async fn trades_by_order(&self, order_hash: B256) -> Result<Vec<TradeRecord>, DataSourceError> {
+        let query = format!(
+            "query TradesByOrder($hash: Bytes!, $lastId: String!) {{ \
+             items: trades(where: {{ order_: {{ orderHash: $hash }}, id_gt: $lastId }}, \
+             first: {MAX_SUBGRAPH_PAGE}, orderBy: id) {{ ...TradeFields }} }} {TRADE_FIELDS}"
+        );
+        let variables = json!({ "hash": order_hash, "lastId": "" });
+        let trades: Vec<SgTrade> = self.all_by_id(&query, variables).await?;
+        let mut trades: Vec<TradeRecord> = trades
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<_, _>>()?;
+        // Pages come back in id order, so restore newest first here.
+        trades.sort_by_key(|trade| Reverse(trade.transaction.timestamp));
+        Ok(trades)
+    }
+
+    async fn orders_by_owner(
+        &self,
+        owner: Address,
+        page: PageRequest,
+    ) -> Result<Page<OrderRecord>, DataSourceError> {
+        let query = format!(
+            "query OrdersByOwner($owner: Bytes!, $skip: Int!, $first: Int!) {{ \
+             orders(where: {{ owner: $owner }}, skip: $skip, first: $first, \
+             orderBy: timestampAdded, orderDirection: desc) {{ ...OrderFields }} }} {ORDER_FIELDS}"
+        );
+        let variables = json!({ "owner": owner, "skip": page.skip(), "first": page.page_size });
+        let data: OrdersData = self.query(&query, variables).await?;
+        let total = self
+            .count_by_owner("orders", "owner: $owner", owner)
+            .await?;
+        Ok(Page {
+            items: data
+                .orders
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+            total,
+        })
+    }
+
+    async fn orders_by_tx(
+        &self,
+        tx_hash: B256,
+    ) -> Result<Option<TransactionOrders>, DataSourceError> {
+        let Some(transaction) = self.transaction(tx_hash).await? else {
+            return Ok(None);
+        };
+        let query = format!(
+            "query OrdersByTx($tx: String!) {{ addOrders(where: {{ transaction: $tx }}) {{ order {{ ...OrderFields }} }} }} {ORDER_FIELDS}"
+        );
+        let data: AddOrdersData = self.query(&query, json!({ "tx": tx_hash })).await?;
+        Ok(Some(TransactionOrders {
+            transaction,
+            orders: data
+                .add_orders
+                .into_iter()
+                .map(|event| event.order.try_into())
+                .collect::<Result<_, _>>()?,
+        }))
+    }
+
+    async fn orders_by_pair(
+        &self,
+        input_token: Address,
+        output_token: Address,
+    ) -> Result<Vec<OrderRecord>, DataSourceError> {
+        let query = format!(
+            "query OrdersByPair($input: Bytes!, $output: Bytes!, $lastId: String!) {{ \
+             items: orders(where: {{ active: true, inputs_: {{ token_: {{ address: $input }} }}, \
+             outputs_: {{ token_: {{ address: $output }} }}, id_gt: $lastId }}, \
+             first: {MAX_SUBGRAPH_PAGE}, orderBy: id) {{ id ...OrderFields }} }} {ORDER_FIELDS}"
+        );
+        let variables = json!({ "input": input_token, "output": output_token, "lastId": "" });
+        let orders: Vec<SgOrder> = self.all_by_id(&query, variables).await?;
+        let orders: Vec<OrderRecord> = orders
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<_, _>>()?;
+        // The subgraph matches any IO, but quotes and takes use the first.
+        Ok(orders
+            .into_iter()
+            .filter(|order| {
+                order.input.token.address == input_token
+                    && order.output.token.address == output_token
+            })
+            .collect())
+    }
+
+    async fn active_orders_using_vault(
+        &self,
+        owner: Address,
+        token: Address,
+        vault_id: U256,
+    ) -> Result<Vec<OrderRecord>, DataSourceError> {
+        let query = format!(
+            "query OrdersUsingVault($owner: Bytes!, $token: Bytes!, $vault: BigInt!) {{ \
+             orders(where: {{ owner: $owner, active: true, or: [\
+             {{ inputs_: {{ token_: {{ address: $token }}, vaultId: $vault }} }}, \
+             {{ outputs_: {{ token_: {{ address: $token }}, vaultId: $vault }} }}] }}, \
+             first: {MAX_SUBGRAPH_PAGE}) {{ ...OrderFields }} }} {ORDER_FIELDS}"
+        );
+        let variables = json!({ "owner": owner, "token": token, "vault": vault_id.to_string() });
+        let data: OrdersData = self.query(&query, variables).await?;
+        data.orders.into_iter().map(TryInto::try_into).collect()
+    }
+
+    async fn trades_by_tx(
+        &self,
+        tx_hash: B256,
+    ) -> Result<Option<TransactionTrades>, DataSourceError> {
+        let Some(transaction) = self.transaction(tx_hash).await? else {
+            return Ok(None);
+        };
+        let query = format!(
+            "query TradesByTx($tx: String!) {{ trades(where: {{ tradeEvent_: {{ transaction: $tx }} }}, first: {MAX_SUBGRAPH_PAGE}) {{ ...TradeFields }} }} {TRADE_FIELDS}"
+        );
+        let data: TradesData = self.query(&query, json!({ "tx": tx_hash })).await?;
+        Ok(Some(TransactionTrades {
+            transaction,
+            limits: self.take_orders_limits(tx_hash).await?,
+            trades: data
+                .trades
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        }))
+    }
+
+    async fn trades_by_owner(
+        &self,
+        owner: Address,
+        range: TimeRange,
+        page: PageRequest,
+    ) -> Result<Page<TradeRecord>, DataSourceError> {
+        let mut filter = String::from("order_: { owner: $owner }");
+        if let Some(start) = range.start {
+            filter.push_str(&format!(", timestamp_gte: \"{start}\""));
+        }
+        if let Some(end) = range.end {
+            filter.push_str(&format!(", timestamp_lt: \"{end}\""));
+        }
+        let query = format!(
+            "query TradesByOwner($owner: Bytes!, $skip: Int!, $first: Int!) {{ \
+             trades(where: {{ {filter} }}, skip: $skip, first: $first, \
+             orderBy: timestamp, orderDirection: desc) {{ ...TradeFields }} }} {TRADE_FIELDS}"
+        );
+        let variables = json!({ "owner": owner, "skip": page.skip(), "first": page.page_size });
+        let data: TradesData = self.query(&query, variables).await?;
+        let total = self.count_by_owner("trades", &filter, owner).await?;
+        Ok(Page {
+            items: data
+                .trades
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+            total,
+        })
+    }
+
+    async fn chain_transaction(&self, tx_hash: B256) -> Result<ChainTransaction, DataSourceError> {
+        let tx: Option<RpcTransaction> = self
+            .rpc("eth_getTransactionByHash", json!([tx_hash]))
+            .await?;
+        Ok(match tx {
+            None => ChainTransaction::Unknown,
+            Some(RpcTransaction {
+                block_number: None, ..
+            }) => ChainTransaction::Pending,
+            Some(RpcTransaction {
+                block_number: Some(block_number),
+                ..
+            }) => ChainTransaction::Mined {
+                block_number: block_number.to(),
+            },
+        })
+    }
+
+    async fn indexed_block(&self) -> Result<u64, DataSourceError> {
+        let data: MetaData = self
+            .query("{ _meta { block { number } } }", json!({}))
+            .await?;
+        Ok(data.meta.block.number)
+    }
+
+    async fn chain_head(&self) -> Result<u64, DataSourceError> {
+        let block: Option<U64> = self.rpc("eth_blockNumber", json!([])).await?;
+        block
+            .map(|block| block.to())
+            .ok_or_else(|| DataSourceError::InvalidResponse("eth_blockNumber failed".into()))
+    }
+
+    async fn quote(&self, order: &OrderRecord) -> Result<Option<OrderQuote>, DataSourceError> {
+        let decoded = OrderV3::abi_decode(&order.order_bytes)
+            .map_err(|e| DataSourceError::InvalidResponse(e.to_string()))?;
+        let call = IOrderBookV4::quoteCall {
+            quoteConfig: Quote {
+                order: decoded,
+                inputIOIndex: U256::ZERO,
+                outputIOIndex: U256::ZERO,
+                signedContext: vec![],
+            },
+        };
+        let params =
+            json!([{ "to": order.orderbook, "data": Bytes::from(call.abi_encode()) }, "latest"]);
+        let Some(result) = self.rpc::<Bytes>("eth_call", params).await? else {
+            return Ok(None);
+        };
+        let quote = IOrderBookV4::quoteCall::abi_decode_returns(&result)
+            .map_err(|e| DataSourceError::InvalidResponse(e.to_string()))?;
+        if !quote.exists {
+            return Ok(None);
+        }
+        Ok(Some(OrderQuote {
+            io_ratio: quote.ioRatio,
+            max_output: quote.outputMax,
+        }))
+    }
+
+    async fn parse_rainlang(
+        &self,
+        deployer: Address,
+        rainlang: &str,
+    ) -> Result<Bytes, DataSourceError> {
+        let call = IParserV2::parse2Call {
+            data: Bytes::copy_from_slice(rainlang.as_bytes()),
+        };
+        let params = json!([{ "to": deployer, "data": Bytes::from(call.abi_encode()) }, "latest"]);
+        let Some(result) = self.rpc::<Bytes>("eth_call", params).await? else {
+            return Err(DataSourceError::InvalidResponse(
+                "deployer rejected the rainlang source".into(),
+            ));
+        };
+        IParserV2::parse2Call::abi_decode_returns(&result)
+            .map_err(|e| DataSourceError::InvalidResponse(e.to_string()))
+    }
+}
+
+#[derive(Deserialize)]
+struct GraphQlResponse<T> {
+    data: Option<T>,
+    errors: Option<Vec<GraphQlError>>,
+}
+
+#[derive(Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    code: i64,
+    #[serde(default)]
+    message: String,
+}
+
+/// A reverted eth_call (e.g. an eval error) means no result, not an outage.
+/// Other errors are failures; node messages can echo request details, so
+/// they are logged rather than carried in the error.
+fn rpc_result<T>(method: &str, response: RpcResponse<T>) -> Result<Option<T>, DataSourceError> {
+    match response.error {
+        Some(error) if method == "eth_call" && error.is_revert() => Ok(None),
+        Some(error) => {
+            warn!("{} failed: {} ({})", method, error.message, error.code);
+            Err(DataSourceError::Request(format!(
+                "{method} returned JSON-RPC error {}",
+                error.code
+            )))
+        }
+        None => Ok(response.result),
+    }
+}
+
+impl RpcError {
+    /// EIP-1474 reserves code 3 for execution errors, but some nodes report
+    /// reverts under the generic server error code with only the message.
+    fn is_revert(&self) -> bool {
+        self.code == 3 || self.message.to_ascii_lowercase().contains("revert")
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcTransaction {
+    input: Bytes,
+    block_number: Option<U64>,
+}
+
+#[derive(Deserialize)]
+struct MetaData {
+    #[serde(rename = "_meta")]
+    meta: SgMeta,
+}
+
+#[derive(Deserialize)]
+struct SgMeta {
+    block: SgBlock,
+}
+
+#[derive(Deserialize)]
+struct SgBlock {
+    number: u64,
+}
+
+#[derive(Deserialize)]
+struct RowsData {
+    items: Vec<Value>,
+}
+
+#[derive(Deserialize)]
+struct IdsData {
+    items: Vec<IdItem>,
+}
+
+#[derive(Deserialize)]
+struct IdItem {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct TransactionData {
+    transaction: Option<SgTransaction>,
+}
+
+#[derive(Deserialize)]
+struct OrdersData {
+    orders: Vec<SgOrder>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AddOrdersData {
+    add_orders: Vec<SgAddOrder>,
+}
+
+#[derive(Deserialize)]
+struct SgAddOrder {
+    order: SgOrder,
+}
+
+#[derive(Deserialize)]
+struct TradesData {
+    trades: Vec<SgTrade>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SgTransaction {
+    id: B256,
+    block_number: String,
+    timestamp: String,
+    from: Address,
+}
+
+#[derive(Deserialize)]
+struct SgToken {
+    address: Address,
+    symbol: Option<String>,
+    decimals: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SgVault {
+    vault_id: String,
+    balance: String,
+    token: SgToken,
+}
+
+#[derive(Deserialize)]
+struct SgId {
+    id: Address,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SgOrder {
+    order_hash: B256,
+    owner: Address,
+    order_bytes: Bytes,
+    meta: Option<Bytes>,
+    active: bool,
+    timestamp_added: String,
+    orderbook: SgId,
+    inputs: Vec<SgVault>,
+    outputs: Vec<SgVault>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SgTradeOrder {
+    order_hash: B256,
+    owner: Address,
+}
+
+#[derive(Deserialize)]
+struct SgTradeEvent {
+    sender: Address,
+    transaction: SgTransaction,
+}
+
+#[derive(Deserialize)]
+struct SgBalanceVault {
+    token: SgToken,
+}
+
+#[derive(Deserialize)]
+struct SgBalanceChange {
+    amount: String,
+    vault: SgBalanceVault,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SgTrade {
+    id: String,
+    order: SgTradeOrder,
+    trade_event: SgTradeEvent,
+    input_vault_balance_change: SgBalanceChange,
+    output_vault_balance_change: SgBalanceChange,
+}
+
+fn parse_u64(value: &str) -> Result<u64, DataSourceError> {
+    value
+        .parse()
+        .map_err(|_| DataSourceError::InvalidResponse(format!("invalid integer {value}")))
+}
+
+/// Parses a subgraph BigInt, dropping the sign used for outgoing balance changes.
+fn parse_u256(value: &str) -> Result<U256, DataSourceError> {
+    value
+        .trim_start_matches('-')
+        .parse()
+        .map_err(|_| DataSourceError::InvalidResponse(format!("invalid integer {value}")))
+}
+
+impl TryFrom<SgToken> for TokenRef {
+    type Error = DataSourceError;
+
+    fn try_from(token: SgToken) -> Result<Self, Self::Error> {
+        let decimals = token
+            .decimals
+            .as_deref()
+            .map(|d| {
+                d.parse()
+                    .map_err(|_| DataSourceError::InvalidResponse(format!("invalid decimals {d}")))
+            })
+            .transpose()?
+            .unwrap_or(18);
+        Ok(TokenRef {
+            address: token.address,
+            symbol: token.symbol.unwrap_or_default(),
+            decimals,
+        })
+    }
+}
+
+impl TryFrom<SgVault> for VaultRecord {
+    type Error = DataSourceError;
+
+    fn try_from(vault: SgVault) -> Result<Self, Self::Error> {
+        Ok(VaultRecord {
+            vault_id: parse_u256(&vault.vault_id)?,
+            token: vault.token.try_into()?,
+            balance: parse_u256(&vault.balance)?,
+        })
+    }
+}
+
+impl TryFrom<SgTransaction> for TransactionRecord {
+    type Error = DataSourceError;
+
+    fn try_from(tx: SgTransaction) -> Result<Self, Self::Error> {
+        Ok(TransactionRecord {
+            tx_hash: tx.id,
+            block_number: parse_u64(&tx.block_number)?,
+            timestamp: parse_u64(&tx.timestamp)?,
+            sender: tx.from,
+        })
+    }
+}
+
+impl TryFrom<SgOrder> for OrderRecord {
+    type Error = DataSourceError;
+
+    fn try_from(order: SgOrder) -> Result<Self, Self::Error> {
+        let missing_io =
+            || DataSourceError::InvalidResponse(format!("order {} has no IO", order.order_hash));
+        let input = order.inputs.into_iter().next().ok_or_else(missing_io)?;
+        let output = order.outputs.into_iter().next().ok_or_else(missing_io)?;
+        Ok(OrderRecord {
+            order_hash: order.order_hash,
+            owner: order.owner,
+            orderbook: order.orderbook.id,
+            order_bytes: order.order_bytes,
+            input: input.try_into()?,
+            output: output.try_into()?,
+            order_type: strategy::order_type_from_meta(
+                order.meta.as_ref().map(|meta| meta.as_ref()),
+            ),
+            active: order.active,
+            timestamp_added: parse_u64(&order.timestamp_added)?,
+        })
+    }
+}
+
+impl TryFrom<SgTrade> for TradeRecord {
+    type Error = DataSourceError;
+
+    fn try_from(trade: SgTrade) -> Result<Self, Self::Error> {
+        Ok(TradeRecord {
+            id: trade.id,
+            sender: trade.trade_event.sender,
+            transaction: trade.trade_event.transaction.try_into()?,
+            order_hash: trade.order.order_hash,
+            order_owner: trade.order.owner,
+            input: TradeLeg {
+                token: trade.input_vault_balance_change.vault.token.try_into()?,
+                amount: parse_u256(&trade.input_vault_balance_change.amount)?,
+            },
+            output: TradeLeg {
+                token: trade.output_vault_balance_change.vault.token.try_into()?,
+                amount: parse_u256(&trade.output_vault_balance_change.amount)?,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::order::OrderType;
+
+    #[test]
+    fn test_rpc_error_distinguishes_reverts() {
+        let error = |value: Value| serde_json::from_value::<RpcError>(value).unwrap();
+        assert!(error(json!({ "code": 3, "message": "execution reverted" })).is_revert());
+        assert!(error(json!({ "code": -32000, "message": "Execution reverted: 0x" })).is_revert());
+        assert!(!error(json!({ "code": -32005, "message": "rate limit exceeded" })).is_revert());
+        assert!(!error(json!({ "code": -32603, "message": "internal error" })).is_revert());
+    }
+
+    #[test]
+    fn test_rpc_errors_other_than_reverts_are_failures() {
+        let response = |error: Value| -> RpcResponse<Bytes> {
+            serde_json::from_value(json!({ "jsonrpc": "2.0", "id": 1, "error": error })).unwrap()
+        };
+        let revert = json!({ "code": 3, "message": "execution reverted" });
+        assert!(matches!(
+            rpc_result("eth_call", response(revert.clone())),
+            Ok(None)
+        ));
+        assert!(rpc_result("eth_getTransactionByHash", response(revert)).is_err());
+
+        let limited = json!({ "code": -32005, "message": "rate limited for key sk_live_123" });
+        match rpc_result("eth_call", response(limited)) {
+            Err(DataSourceError::Request(detail)) => {
+                assert_eq!(detail, "eth_call returned JSON-RPC error -32005")
+            }
+            _ => panic!("expected a request error"),
+        }
+    }
+
+    #[test]
+    fn test_parse_order_response() {
+        let json = json!({
+            "orders": [{
+                "orderHash": "0xabcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890",
+                "owner": "0x1234567890abcdef1234567890abcdef12345678",
+                "orderBytes": "0x",
+                "active": true,
+                "timestampAdded": "1718452800",
+                "orderbook": { "id": "0xd2938e7c9fe3597f78832ce780feb61945c377d7" },
+                "inputs": [{
+                    "vaultId": "0x1",
+                    "balance": "1000000",
+                    "token": { "address": "0x833589fcd6edb6e08f4c7c32d4f71b54bda02913", "symbol": "USDC", "decimals": "6" }
+                }],
+                "outputs": [{
+                    "vaultId": "2",
+                    "balance": "500000",
+                    "token": { "address": "0x4200000000000000000000000000000000000006", "symbol": "WETH", "decimals": "18" }
+                }]
+            }]
+        });
+        let data: OrdersData = serde_json::from_value(json).unwrap();
+        let order: OrderRecord = data.orders.into_iter().next().unwrap().try_into().unwrap();
+        assert_eq!(order.timestamp_added, 1718452800);
+        assert!(order.active);
+        assert_eq!(order.input.vault_id, U256::from(1));
+        assert_eq!(order.input.token.decimals, 6);
+        assert_eq!(order.output.vault_id, U256::from(2));
+        assert_eq!(order.order_type, OrderType::Solver);
+        assert_eq!(order.output.balance, U256::from(500000));
+        assert_eq!(order.output.token.symbol, "WETH");
+    }
+
+    #[test]
+    fn test_parse_trade_drops_outgoing_sign() {
+        let json = json!({
+            "trades": [{
+                "id": "trade-1",
+                "order": {
+                    "orderHash": "0xabcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890",
+                    "owner": "0x1234567890abcdef1234567890abcdef12345678"
+                },
+                "tradeEvent": { "sender": "0x2222222222222222222222222222222222222222", "transaction": {
+                    "id": "0x1111111111111111111111111111111111111111111111111111111111111111",
+                    "blockNumber": "12345678",
+                    "timestamp": "1718452800",
+                    "from": "0x1234567890abcdef1234567890abcdef12345678"
+                }},
+                "inputVaultBalanceChange": { "amount": "1000000", "vault": { "token": { "address": "0x833589fcd6edb6e08f4c7c32d4f71b54bda02913", "symbol": "USDC", "decimals": "6" } } },
+                "outputVaultBalanceChange": { "amount": "-500000000000000", "vault": { "token": { "address": "0x4200000000000000000000000000000000000006", "symbol": "WETH", "decimals": "18" } } }
+            }]
+        });
+        let data: TradesData = serde_json::from_value(json).unwrap();
+        let trade: TradeRecord = data.trades.into_iter().next().unwrap().try_into().unwrap();
+        assert_eq!(trade.id, "trade-1");
+        assert_eq!(trade.sender, Address::repeat_byte(0x22));
+        assert_eq!(trade.transaction.block_number, 12345678);
+        assert_eq!(trade.input.amount, U256::from(1000000));
+        assert_eq!(trade.output.amount, U256::from(500000000000000u64));
+    }
+
+    #[test]
+    fn test_parse_rpc_transaction() {
+        let pending: RpcTransaction =
+            serde_json::from_value(json!({ "input": "0x", "blockNumber": null })).unwrap();
+        assert!(pending.block_number.is_none());
+        let mined: RpcTransaction =
+            serde_json::from_value(json!({ "input": "0x", "blockNumber": "0x1b4" })).unwrap();
+        assert_eq!(mined.block_number, Some(U64::from(436)));
+    }
+
+    #[test]
+    fn test_order_without_io_is_rejected() {
+        let json = json!({
+            "orders": [{
+                "orderHash": "0xabcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890",
+                "owner": "0x1234567890abcdef1234567890abcdef12345678",
+                "orderBytes": "0x",
+                "active": true,
+                "timestampAdded": "1718452800",
+                "orderbook": { "id": "0xd2938e7c9fe3597f78832ce780feb61945c377d7" },
+                "inputs": [],
+                "outputs": []
+            }]
+        });
+        let data: OrdersData = serde_json::from_value(json).unwrap();
+        let result: Result<OrderRecord, _> = data.orders.into_iter().next().unwrap().try_into();
+        assert!(matches!(result, Err(DataSourceError::InvalidResponse(_))));
+    }
+}
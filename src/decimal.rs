@@ -0,0 +1,206 @@
+//! Exact fixed-point helpers for token amounts and IO ratios.
+//!
+//! Amounts are base-unit integers in their token's decimals. Ratios are
+//! 18-decimal fixed point over human-readable units, matching the orderbook.
+
+use alloy::primitives::utils::{format_units, parse_units, ParseUnits};
+use alloy::primitives::U256;
+
+pub const RATIO_DECIMALS: u8 = 18;
+
+/// Rescales a base-unit amount between decimals, truncating when narrowing.
+pub fn rescale(amount: U256, from_decimals: u8, to_decimals: u8) -> U256 {
+    if from_decimals == to_decimals {
+        amount
+    } else if from_decimals < to_decimals {
+        amount * U256::from(10).pow(U256::from(to_decimals - from_decimals))
+    } else {
+        amount / U256::from(10).pow(U256::from(from_decimals - to_decimals))
+    }
+}
+
+/// [`rescale`] that returns `None` instead of wrapping when widening overflows.
+fn checked_rescale(amount: U256, from_decimals: u8, to_decimals: u8) -> Option<U256> {
+    if from_decimals < to_decimals {
+        amount.checked_mul(U256::from(10).pow(U256::from(to_decimals - from_decimals)))
+    } else {
+        Some(rescale(amount, from_decimals, to_decimals))
+    }
+}
+
+/// Input per unit of output as an 18-decimal ratio. `None` when output is
+/// zero or the ratio does not fit in 256 bits.
+pub fn io_ratio(
+    input: U256,
+    input_decimals: u8,
+    output: U256,
+    output_decimals: u8,
+) -> Option<U256> {
+    let input = checked_rescale(input, input_decimals, RATIO_DECIMALS)?;
+    let output = checked_rescale(output, output_decimals, RATIO_DECIMALS)?;
+    if output.is_zero() {
+        return None;
+    }
+    Some(input.checked_mul(U256::from(10).pow(U256::from(RATIO_DECIMALS)))? / output)
+}
+
+/// Input owed for `output` at an 18-decimal `ratio`, rounded up so a quote
+/// never understates what the taker pays. `None` when the input does not fit
+/// in 256 bits.
+pub fn input_for_output(
+    output: U256,
+    output_decimals: u8,
+    ratio: U256,
+    input_decimals: u8,
+) -> Option<U256> {
+    let output = checked_rescale(output, output_decimals, RATIO_DECIMALS)?;
+    let input = output
+        .checked_mul(ratio)?
+        .div_ceil(U256::from(10).pow(U256::from(RATIO_DECIMALS)));
+    if input_decimals < RATIO_DECIMALS {
+        Some(input.div_ceil(U256::from(10).pow(U256::from(RATIO_DECIMALS - input_decimals))))
+    } else {
+        checked_rescale(input, RATIO_DECIMALS, input_decimals)
+    }
+}
+
+/// Parses a human-readable ratio such as `0.0005` into 18-decimal fixed
+/// point. Negative values and excess precision are rejected.
+pub fn parse_ratio(value: &str) -> Option<U256> {
+    // `parse_units` silently truncates digits beyond the requested decimals.
+    let fraction = value.split_once('.').map_or("", |(_, fraction)| fraction);
+    if fraction.len() > usize::from(RATIO_DECIMALS) {
+        return None;
+    }
+    match parse_units(value, RATIO_DECIMALS) {
+        Ok(ParseUnits::U256(ratio)) => Some(ratio),
+        _ => None,
+    }
+}
+
+/// Formats an 18-decimal ratio without trailing zeros, e.g. `0.0005`.
+pub fn format_ratio(ratio: U256) -> String {
+    format_decimal(ratio, RATIO_DECIMALS)
+}
+
+/// Formats a base-unit amount scaled by `decimals`, without trailing zeros.
+pub fn format_decimal(value: U256, decimals: u8) -> String {
+    let formatted = format_units(value, decimals).unwrap_or_else(|_| value.to_string());
+    match formatted.split_once('.') {
+        Some((whole, fraction)) => {
+            let fraction = fraction.trim_end_matches('0');
+            if fraction.is_empty() {
+                whole.to_string()
+            } else {
+                format!("{whole}.{fraction}")
+            }
+        }
+        None => formatted,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rescale() {
+        assert_eq!(
+            rescale(U256::from(1_500_000), 6, 18),
+            U256::from(1_500_000_000_000_000_000u128)
+        );
+        assert_eq!(
+            rescale(U256::from(1_500_000_000_000_000_000u128), 18, 6),
+            U256::from(1_500_000)
+        );
+        assert_eq!(rescale(U256::from(42), 8, 8), U256::from(42));
+        assert_eq!(rescale(U256::from(999), 3, 0), U256::ZERO);
+    }
+
+    #[test]
+    fn test_io_ratio_mixed_decimals() {
+        // 1 USDC (6 decimals) in for 0.0005 WETH (18 decimals) out.
+        let ratio = io_ratio(
+            U256::from(1_000_000),
+            6,
+            U256::from(500_000_000_000_000u128),
+            18,
+        )
+        .unwrap();
+        assert_eq!(format_ratio(ratio), "2000");
+
+        let ratio = io_ratio(
+            U256::from(500_000_000_000_000u128),
+            18,
+            U256::from(1_000_000),
+            6,
+        )
+        .unwrap();
+        assert_eq!(format_ratio(ratio), "0.0005");
+    }
+
+    #[test]
+    fn test_io_ratio_zero_output() {
+        assert!(io_ratio(U256::from(1), 18, U256::ZERO, 18).is_none());
+    }
+
+    #[test]
+    fn test_input_for_output_rounds_up() {
+        // 0.5 WETH at 2000 USDC per WETH.
+        let ratio = U256::from(2000) * U256::from(10).pow(U256::from(18));
+        assert_eq!(
+            input_for_output(U256::from(500_000_000_000_000_000u128), 18, ratio, 6),
+            Some(U256::from(1_000_000_000))
+        );
+        // 1 wei of WETH still costs a whole base unit of USDC.
+        assert_eq!(
+            input_for_output(U256::from(1), 18, ratio, 6),
+            Some(U256::from(1))
+        );
+        // 1 USDC at 0.0005 WETH per USDC.
+        let ratio = U256::from(500_000_000_000_000u128);
+        assert_eq!(
+            input_for_output(U256::from(1_000_000), 6, ratio, 18),
+            Some(U256::from(500_000_000_000_000u128))
+        );
+    }
+
+    #[test]
+    fn test_overflow_is_reported() {
+        let ratio = U256::from(2000) * U256::from(10).pow(U256::from(18));
+        assert_eq!(
+            input_for_output(U256::MAX - U256::from(1), 18, ratio, 6),
+            None
+        );
+        // Widening a 6-decimal amount to 18 decimals overflows first.
+        assert_eq!(
+            input_for_output(U256::MAX / U256::from(10), 6, U256::from(1), 6),
+            None
+        );
+        assert_eq!(io_ratio(U256::MAX, 18, U256::from(1), 18), None);
+    }
+
+    #[test]
+    fn test_parse_ratio() {
+        assert_eq!(
+            parse_ratio("0.0005"),
+            Some(U256::from(500_000_000_000_000u128))
+        );
+        assert_eq!(
+            parse_ratio("2000"),
+            Some(U256::from(2000) * U256::from(10).pow(U256::from(18)))
+        );
+        assert_eq!(parse_ratio("-1"), None);
+        assert_eq!(parse_ratio("abc"), None);
+        assert_eq!(parse_ratio("0.0000000000000000001"), None);
+    }
+
+    #[test]
+    fn test_format_decimal_trims_zeros() {
+        assert_eq!(format_decimal(U256::from(1_000_000), 6), "1");
+        assert_eq!(format_decimal(U256::from(1_500_000), 6), "1.5");
+        assert_eq!(format_decimal(U256::from(1), 18), "0.000000000000000001");
+        assert_eq!(format_decimal(U256::ZERO, 18), "0");
+        assert_eq!(format_decimal(U256::from(42), 0), "42");
+    }
+}
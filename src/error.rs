@@ -1,56 +1,260 @@
-use rocket::http::Status;
+use crate::access_log;
+use rocket::http::{Header, Status};
 use rocket::response::Responder;
 use rocket::serde::json::Json;
-use rocket::{Request, Response};
+use rocket::{Catcher, Request, Response};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+/// Stable, machine-readable error codes. Clients should match on these
+/// rather than on messages, which may change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// The request is malformed, e.g. an unparseable body.
+    BadRequest,
+    /// No valid credentials were supplied.
+    Unauthorized,
+    /// The credentials lack permission for this operation.
+    Forbidden,
+    NotFound,
+    /// The request conflicts with the resource's current state.
+    Conflict,
+    /// The request is well-formed but has invalid fields, listed in `details.fields`.
+    UnprocessableEntity,
+    /// Open orders cannot fill the requested amount.
+    InsufficientLiquidity,
+    /// Rate limit exceeded; retry after `details.retryAfterSeconds`.
+    TooManyRequests,
+    /// The transaction exists but is not indexed yet; retry after `details.retryAfterSeconds`.
+    NotYetIndexed,
+    /// An upstream dependency is unavailable; retry after `details.retryAfterSeconds`.
+    ServiceUnavailable,
+    InternalError,
+    /// Any other HTTP error raised outside the API's handlers.
+    HttpError,
+}
+
+impl ErrorCode {
+    fn from_status(status: Status) -> Self {
+        match status.code {
+            400 => ErrorCode::BadRequest,
+            401 => ErrorCode::Unauthorized,
+            403 => ErrorCode::Forbidden,
+            404 => ErrorCode::NotFound,
+            409 => ErrorCode::Conflict,
+            422 => ErrorCode::UnprocessableEntity,
+            429 => ErrorCode::TooManyRequests,
+            500 => ErrorCode::InternalError,
+            503 => ErrorCode::ServiceUnavailable,
+            _ => ErrorCode::HttpError,
+        }
+    }
+}
+
+/// A single invalid request field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct FieldError {
+    #[schema(example = "floorIo")]
+    pub field: String,
+    #[schema(example = "must not exceed startIo")]
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiErrorDetails {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
+    /// Seconds to wait before retrying, mirroring the `Retry-After` header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = 5)]
+    pub retry_after_seconds: Option<u64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
 pub struct ApiErrorDetail {
     #[schema(example = "BAD_REQUEST")]
-    pub code: String,
+    pub code: ErrorCode,
     #[schema(example = "Something went wrong")]
     pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<ApiErrorDetails>,
+    /// Matches the `X-Request-Id` response header and the access log line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "3f2a9c1e6b7d4e58a0c1d2e3f4a5b6c7")]
+    pub request_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-#[schema(example = json!({"error": {"code": "BAD_REQUEST", "message": "Something went wrong"}}))]
+#[schema(example = json!({"error": {"code": "BAD_REQUEST", "message": "Something went wrong", "requestId": "3f2a9c1e6b7d4e58a0c1d2e3f4a5b6c7"}}))]
 pub struct ApiErrorResponse {
     pub error: ApiErrorDetail,
 }
 
+/// Seconds a client should wait before polling a not-yet-indexed transaction again.
+pub const NOT_YET_INDEXED_RETRY_AFTER: u64 = 5;
+/// Seconds a client should wait before retrying while an upstream is unavailable.
+pub const SERVICE_UNAVAILABLE_RETRY_AFTER: u64 = 30;
+
 #[derive(Debug, thiserror::Error)]
 pub enum ApiError {
     #[error("Bad request: {0}")]
     BadRequest(String),
     #[error("Unauthorized: {0}")]
     Unauthorized(String),
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
     #[error("Not found: {0}")]
     NotFound(String),
+    #[error("Conflict: {0}")]
+    Conflict(String),
+    /// Every invalid field in a request, reported together.
+    #[error("Invalid request: {0:?}")]
+    UnprocessableEntity(Vec<FieldError>),
+    #[error("Insufficient liquidity: {0}")]
+    InsufficientLiquidity(String),
+    #[error("Too many requests: {message}")]
+    TooManyRequests { message: String, retry_after: u64 },
+    #[error("Not yet indexed: {0}")]
+    NotYetIndexed(String),
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(String),
     #[error("Internal error: {0}")]
     Internal(String),
 }
 
+impl ApiError {
+    pub fn status(&self) -> Status {
+        match self {
+            ApiError::BadRequest(_) | ApiError::InsufficientLiquidity(_) => Status::BadRequest,
+            ApiError::Unauthorized(_) => Status::Unauthorized,
+            ApiError::Forbidden(_) => Status::Forbidden,
+            ApiError::NotFound(_) => Status::NotFound,
+            ApiError::Conflict(_) => Status::Conflict,
+            ApiError::UnprocessableEntity(_) => Status::UnprocessableEntity,
+            ApiError::TooManyRequests { .. } => Status::TooManyRequests,
+            ApiError::NotYetIndexed(_) => Status::Accepted,
+            ApiError::ServiceUnavailable(_) => Status::ServiceUnavailable,
+            ApiError::Internal(_) => Status::InternalServerError,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ApiError::BadRequest(_) => ErrorCode::BadRequest,
+            ApiError::Unauthorized(_) => ErrorCode::Unauthorized,
+            ApiError::Forbidden(_) => ErrorCode::Forbidden,
+            ApiError::NotFound(_) => ErrorCode::NotFound,
+            ApiError::Conflict(_) => ErrorCode::Conflict,
+            ApiError::UnprocessableEntity(_) => ErrorCode::UnprocessableEntity,
+            ApiError::InsufficientLiquidity(_) => ErrorCode::InsufficientLiquidity,
+            ApiError::TooManyRequests { .. } => ErrorCode::TooManyRequests,
+            ApiError::NotYetIndexed(_) => ErrorCode::NotYetIndexed,
+            ApiError::ServiceUnavailable(_) => ErrorCode::ServiceUnavailable,
+            ApiError::Internal(_) => ErrorCode::InternalError,
+        }
+    }
+
+    fn retry_after(&self) -> Option<u64> {
+        match self {
+            ApiError::TooManyRequests { retry_after, .. } => Some(*retry_after),
+            ApiError::NotYetIndexed(_) => Some(NOT_YET_INDEXED_RETRY_AFTER),
+            ApiError::ServiceUnavailable(_) => Some(SERVICE_UNAVAILABLE_RETRY_AFTER),
+            _ => None,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            ApiError::BadRequest(msg)
+            | ApiError::Unauthorized(msg)
+            | ApiError::Forbidden(msg)
+            | ApiError::NotFound(msg)
+            | ApiError::Conflict(msg)
+            | ApiError::InsufficientLiquidity(msg)
+            | ApiError::TooManyRequests { message: msg, .. }
+            | ApiError::NotYetIndexed(msg)
+            | ApiError::ServiceUnavailable(msg)
+            | ApiError::Internal(msg) => msg.clone(),
+            ApiError::UnprocessableEntity(fields) => format!("{} invalid field(s)", fields.len()),
+        }
+    }
+
+    fn details(self) -> Option<ApiErrorDetails> {
+        let retry_after_seconds = self.retry_after();
+        let fields = match self {
+            ApiError::UnprocessableEntity(fields) => fields,
+            _ => Vec::new(),
+        };
+        (!fields.is_empty() || retry_after_seconds.is_some()).then_some(ApiErrorDetails {
+            fields,
+            retry_after_seconds,
+        })
+    }
+}
+
+impl From<rocket::serde::json::Error<'_>> for ApiError {
+    fn from(e: rocket::serde::json::Error<'_>) -> Self {
+        match e {
+            rocket::serde::json::Error::Io(e) => {
+                ApiError::BadRequest(format!("failed to read request body: {e}"))
+            }
+            rocket::serde::json::Error::Parse(_, e) => {
+                ApiError::BadRequest(format!("invalid request body: {e}"))
+            }
+        }
+    }
+}
+
 impl<'r> Responder<'r, 'static> for ApiError {
     fn respond_to(self, req: &'r Request<'_>) -> rocket::response::Result<'static> {
-        let (status, code, message) = match &self {
-            ApiError::BadRequest(msg) => (Status::BadRequest, "BAD_REQUEST", msg.clone()),
-            ApiError::Unauthorized(msg) => (Status::Unauthorized, "UNAUTHORIZED", msg.clone()),
-            ApiError::NotFound(msg) => (Status::NotFound, "NOT_FOUND", msg.clone()),
-            ApiError::Internal(msg) => (Status::InternalServerError, "INTERNAL_ERROR", msg.clone()),
-        };
+        let status = self.status();
+        let retry_after = self.retry_after();
         let body = ApiErrorResponse {
             error: ApiErrorDetail {
-                code: code.to_string(),
-                message,
+                code: self.code(),
+                message: self.message(),
+                details: self.details(),
+                request_id: access_log::request_id(req).map(String::from),
             },
         };
-        Response::build_from(Json(body).respond_to(req)?)
-            .status(status)
-            .ok()
+        let mut response = Response::build_from(Json(body).respond_to(req)?);
+        response.status(status);
+        if let Some(retry_after) = retry_after {
+            response.header(Header::new("Retry-After", retry_after.to_string()));
+        }
+        response.ok()
     }
 }
 
+/// Renders errors Rocket raises itself (unmatched routes, unparseable path
+/// or query parameters, handler panics) in the same envelope as [`ApiError`].
+#[catch(default)]
+fn default_catcher(status: Status, req: &Request<'_>) -> (Status, Json<ApiErrorResponse>) {
+    let message = match status.code {
+        400 => format!("malformed request to {}", req.uri()),
+        404 => format!("no route for {} {}", req.method(), req.uri()),
+        422 => format!("invalid path or query parameter in {}", req.uri()),
+        500 => "internal server error".to_string(),
+        _ => status.reason().unwrap_or("unexpected error").to_string(),
+    };
+    let body = ApiErrorResponse {
+        error: ApiErrorDetail {
+            code: ErrorCode::from_status(status),
+            message,
+            details: None,
+            request_id: access_log::request_id(req).map(String::from),
+        },
+    };
+    (status, Json(body))
+}
+
+pub fn catchers() -> Vec<Catcher> {
+    rocket::catchers![default_catcher]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -60,24 +264,95 @@ mod tests {
     fn bad_request() -> Result<(), ApiError> {
         Err(ApiError::BadRequest("invalid input".into()))
     }
+    #[get("/validation")]
+    fn validation() -> Result<(), ApiError> {
+        Err(ApiError::UnprocessableEntity(vec![
+            FieldError {
+                field: "period".into(),
+                message: "must be positive".into(),
+            },
+            FieldError {
+                field: "floorIo".into(),
+                message: "must not exceed startIo".into(),
+            },
+        ]))
+    }
     #[get("/unauthorized")]
     fn unauthorized() -> Result<(), ApiError> {
         Err(ApiError::Unauthorized("no token".into()))
     }
+    #[get("/forbidden")]
+    fn forbidden() -> Result<(), ApiError> {
+        Err(ApiError::Forbidden("missing deploy scope".into()))
+    }
+    #[get("/conflict")]
+    fn conflict() -> Result<(), ApiError> {
+        Err(ApiError::Conflict("order already cancelled".into()))
+    }
+    #[get("/insufficient-liquidity")]
+    fn insufficient_liquidity() -> Result<(), ApiError> {
+        Err(ApiError::InsufficientLiquidity(
+            "only 1 of 2 available".into(),
+        ))
+    }
+    #[get("/too-many-requests")]
+    fn too_many_requests() -> Result<(), ApiError> {
+        Err(ApiError::TooManyRequests {
+            message: "slow down".into(),
+            retry_after: 12,
+        })
+    }
+    #[get("/service-unavailable")]
+    fn service_unavailable() -> Result<(), ApiError> {
+        Err(ApiError::ServiceUnavailable("subgraph down".into()))
+    }
     #[get("/not-found")]
     fn not_found() -> Result<(), ApiError> {
         Err(ApiError::NotFound("order not found".into()))
     }
+    #[get("/not-yet-indexed")]
+    fn not_yet_indexed() -> Result<(), ApiError> {
+        Err(ApiError::NotYetIndexed("tx pending".into()))
+    }
     #[get("/internal")]
     fn internal() -> Result<(), ApiError> {
         Err(ApiError::Internal("something broke".into()))
     }
+    #[get("/panic")]
+    fn panics() -> &'static str {
+        panic!("handler bug")
+    }
+    #[get("/teapot")]
+    fn teapot() -> Status {
+        Status::ImATeapot
+    }
+    #[get("/typed/<id>")]
+    fn typed(id: u32) -> String {
+        id.to_string()
+    }
 
     fn error_client() -> Client {
-        let rocket = rocket::build().mount(
-            "/",
-            rocket::routes![bad_request, unauthorized, not_found, internal],
-        );
+        let rocket = rocket::build()
+            .mount(
+                "/",
+                rocket::routes![
+                    bad_request,
+                    validation,
+                    unauthorized,
+                    forbidden,
+                    conflict,
+                    insufficient_liquidity,
+                    too_many_requests,
+                    service_unavailable,
+                    not_found,
+                    not_yet_indexed,
+                    internal,
+                    panics,
+                    teapot,
+                    typed
+                ],
+            )
+            .register("/", catchers());
         Client::tracked(rocket).expect("valid rocket instance")
     }
 
@@ -102,18 +377,136 @@ mod tests {
         assert_error_response(&client, "/bad-request", 400, "BAD_REQUEST", "invalid input");
     }
 
+    #[test]
+    fn test_validation_lists_every_field() {
+        let client = error_client();
+        assert_error_response(
+            &client,
+            "/validation",
+            422,
+            "UNPROCESSABLE_ENTITY",
+            "2 invalid field(s)",
+        );
+        let response = client.get("/validation").dispatch();
+        let body: serde_json::Value =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(
+            body["error"]["details"]["fields"],
+            serde_json::json!([
+                { "field": "period", "message": "must be positive" },
+                { "field": "floorIo", "message": "must not exceed startIo" },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_bad_request_omits_details() {
+        let client = error_client();
+        let response = client.get("/bad-request").dispatch();
+        let body: serde_json::Value =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        assert!(body["error"].get("details").is_none());
+    }
+
     #[test]
     fn test_unauthorized_returns_401() {
         let client = error_client();
         assert_error_response(&client, "/unauthorized", 401, "UNAUTHORIZED", "no token");
     }
 
+    #[test]
+    fn test_forbidden_returns_403() {
+        let client = error_client();
+        assert_error_response(
+            &client,
+            "/forbidden",
+            403,
+            "FORBIDDEN",
+            "missing deploy scope",
+        );
+    }
+
+    #[test]
+    fn test_conflict_returns_409() {
+        let client = error_client();
+        assert_error_response(
+            &client,
+            "/conflict",
+            409,
+            "CONFLICT",
+            "order already cancelled",
+        );
+    }
+
+    #[test]
+    fn test_insufficient_liquidity_returns_400() {
+        let client = error_client();
+        assert_error_response(
+            &client,
+            "/insufficient-liquidity",
+            400,
+            "INSUFFICIENT_LIQUIDITY",
+            "only 1 of 2 available",
+        );
+    }
+
+    #[test]
+    fn test_too_many_requests_returns_429_with_retry_hint() {
+        let client = error_client();
+        assert_error_response(
+            &client,
+            "/too-many-requests",
+            429,
+            "TOO_MANY_REQUESTS",
+            "slow down",
+        );
+        let response = client.get("/too-many-requests").dispatch();
+        assert_eq!(response.headers().get_one("Retry-After"), Some("12"));
+        let body: serde_json::Value =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(
+            body["error"]["details"],
+            serde_json::json!({ "retryAfterSeconds": 12 })
+        );
+    }
+
+    #[test]
+    fn test_service_unavailable_returns_503_with_retry_hint() {
+        let client = error_client();
+        assert_error_response(
+            &client,
+            "/service-unavailable",
+            503,
+            "SERVICE_UNAVAILABLE",
+            "subgraph down",
+        );
+        let response = client.get("/service-unavailable").dispatch();
+        assert_eq!(response.headers().get_one("Retry-After"), Some("30"));
+    }
+
     #[test]
     fn test_not_found_returns_404() {
         let client = error_client();
         assert_error_response(&client, "/not-found", 404, "NOT_FOUND", "order not found");
     }
 
+    #[test]
+    fn test_not_yet_indexed_returns_202_with_retry_after() {
+        let client = error_client();
+        assert_error_response(
+            &client,
+            "/not-yet-indexed",
+            202,
+            "NOT_YET_INDEXED",
+            "tx pending",
+        );
+        let response = client.get("/not-yet-indexed").dispatch();
+        assert_eq!(response.headers().get_one("Retry-After"), Some("5"));
+        let body: serde_json::Value =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(body["error"]["details"]["retryAfterSeconds"], 5);
+    }
+
     #[test]
     fn test_internal_returns_500() {
         let client = error_client();
@@ -125,4 +518,59 @@ mod tests {
             "something broke",
         );
     }
+
+    #[test]
+    fn test_unknown_route_returns_json_404() {
+        let client = error_client();
+        assert_error_response(
+            &client,
+            "/missing",
+            404,
+            "NOT_FOUND",
+            "no route for GET /missing",
+        );
+    }
+
+    #[test]
+    fn test_unparseable_param_returns_json_422() {
+        let client = error_client();
+        assert_error_response(
+            &client,
+            "/typed/abc",
+            422,
+            "UNPROCESSABLE_ENTITY",
+            "invalid path or query parameter in /typed/abc",
+        );
+    }
+
+    #[test]
+    fn test_panic_returns_json_500() {
+        let client = error_client();
+        assert_error_response(
+            &client,
+            "/panic",
+            500,
+            "INTERNAL_ERROR",
+            "internal server error",
+        );
+    }
+
+    #[test]
+    fn test_other_status_keeps_envelope() {
+        let client = error_client();
+        assert_error_response(&client, "/teapot", 418, "HTTP_ERROR", "I'm a teapot");
+    }
+
+    #[test]
+    fn test_error_codes_serialize_screaming_snake_case() {
+        let codes = [
+            (ErrorCode::InsufficientLiquidity, "INSUFFICIENT_LIQUIDITY"),
+            (ErrorCode::NotYetIndexed, "NOT_YET_INDEXED"),
+            (ErrorCode::InternalError, "INTERNAL_ERROR"),
+            (ErrorCode::HttpError, "HTTP_ERROR"),
+        ];
+        for (code, expected) in codes {
+            assert_eq!(serde_json::to_value(code).unwrap(), expected);
+        }
+    }
 }
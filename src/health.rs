@@ -0,0 +1,232 @@
+//! Readiness probes against the upstreams behind the data routes.
+//!
+//! Both the RPC node and the subgraph are critical: if either cannot be
+//! reached in time the service is unavailable. An index trailing the chain
+//! by more than `max_block_lag` blocks only degrades it.
+//!
+//! The endpoint is public, so a result is reused for `cache_ms` and
+//! concurrent requests wait on a single round of probes.
+
+use crate::data_source::{DataSourceError, OrderbookDataSource};
+use crate::metrics::Metrics;
+use crate::types::health::{DependencyHealth, DependencyStatus, HealthResponse, HealthStatus};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthSettings {
+    /// How long each probe may take before its dependency counts as down.
+    #[serde(default = "default_probe_timeout_ms")]
+    pub probe_timeout_ms: u64,
+    #[serde(default = "default_max_block_lag")]
+    pub max_block_lag: u64,
+    /// How long a readiness result is served before probing again.
+    #[serde(default = "default_cache_ms")]
+    pub cache_ms: u64,
+}
+
+impl Default for HealthSettings {
+    fn default() -> Self {
+        Self {
+            probe_timeout_ms: default_probe_timeout_ms(),
+            max_block_lag: default_max_block_lag(),
+            cache_ms: default_cache_ms(),
+        }
+    }
+}
+
+fn default_probe_timeout_ms() -> u64 {
+    2_000
+}
+
+fn default_max_block_lag() -> u64 {
+    50
+}
+
+fn default_cache_ms() -> u64 {
+    5_000
+}
+
+/// The latest readiness result and when it was taken.
+#[derive(Default)]
+pub struct ReadinessCache(Mutex<Option<(Instant, HealthResponse)>>);
+
+/// [`readiness`], reusing a result younger than `settings.cache_ms`.
+pub async fn cached_readiness(
+    cache: &ReadinessCache,
+    data_source: &dyn OrderbookDataSource,
+    settings: &HealthSettings,
+    metrics: &Metrics,
+) -> HealthResponse {
+    let ttl = Duration::from_millis(settings.cache_ms);
+    // Held across the probes so that concurrent misses share them.
+    let mut latest = cache.0.lock().await;
+    let fresh = latest.as_ref().filter(|(taken, _)| taken.elapsed() < ttl);
+    metrics.record_cache_lookup("readiness", fresh.is_some());
+    if let Some((_, response)) = fresh {
+        return response.clone();
+    }
+    let response = readiness(data_source, settings).await;
+    *latest = Some((Instant::now(), response.clone()));
+    response
+}
+
+pub async fn readiness(
+    data_source: &dyn OrderbookDataSource,
+    settings: &HealthSettings,
+) -> HealthResponse {
+    let timeout = Duration::from_millis(settings.probe_timeout_ms);
+    let (head, indexed) = tokio::join!(
+        probe(timeout, data_source.chain_head()),
+        probe(timeout, data_source.indexed_block()),
+    );
+    let block_lag = match (&head.result, &indexed.result) {
+        (Ok(head), Ok(indexed)) => Some(head.saturating_sub(*indexed)),
+        _ => None,
+    };
+
+    let rpc = head.into_dependency("rpc", None);
+    let mut subgraph = indexed.into_dependency("subgraph", block_lag);
+    if block_lag.is_some_and(|lag| lag > settings.max_block_lag) {
+        subgraph.status = DependencyStatus::Degraded;
+    }
+
+    let dependencies = vec![rpc, subgraph];
+    let status = if dependencies
+        .iter()
+        .any(|dependency| dependency.status == DependencyStatus::Down)
+    {
+        HealthStatus::Unavailable
+    } else if dependencies
+        .iter()
+        .any(|dependency| dependency.status == DependencyStatus::Degraded)
+    {
+        HealthStatus::Degraded
+    } else {
+        HealthStatus::Ok
+    };
+    HealthResponse {
+        status,
+        dependencies,
+    }
+}
+
+struct Probe {
+    result: Result<u64, String>,
+    latency: Duration,
+}
+
+impl Probe {
+    fn into_dependency(self, name: &str, block_lag: Option<u64>) -> DependencyHealth {
+        let (status, block_number, error) = match self.result {
+            Ok(block) => (DependencyStatus::Up, Some(block), None),
+            Err(e) => (DependencyStatus::Down, None, Some(e)),
+        };
+        DependencyHealth {
+            name: name.into(),
+            status,
+            latency_ms: self.latency.as_millis() as u64,
+            block_number,
+            block_lag,
+            error,
+        }
+    }
+}
+
+/// Runs `check` with a deadline. Failures are reported by
+/// [`DataSourceError::public_message`] and the full error is logged.
+async fn probe(
+    timeout: Duration,
+    check: impl Future<Output = Result<u64, DataSourceError>>,
+) -> Probe {
+    let started = Instant::now();
+    let result = match tokio::time::timeout(timeout, check).await {
+        Ok(Ok(block)) => Ok(block),
+        Ok(Err(e)) => {
+            warn!("readiness probe failed: {}", e);
+            Err(e.public_message().to_string())
+        }
+        Err(_) => Err(format!("timed out after {}ms", timeout.as_millis())),
+    };
+    Probe {
+        result,
+        latency: started.elapsed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source::memory::InMemoryDataSource;
+
+    #[rocket::async_test]
+    async fn test_ready_when_index_is_current() {
+        let source = InMemoryDataSource::new()
+            .with_chain_head(1_010)
+            .with_indexed_block(1_000);
+        let health = readiness(&source, &HealthSettings::default()).await;
+        assert_eq!(health.status, HealthStatus::Ok);
+        let subgraph = &health.dependencies[1];
+        assert_eq!(subgraph.name, "subgraph");
+        assert_eq!(subgraph.block_number, Some(1_000));
+        assert_eq!(subgraph.block_lag, Some(10));
+        assert_eq!(health.dependencies[0].block_number, Some(1_010));
+    }
+
+    #[rocket::async_test]
+    async fn test_lagging_index_degrades() {
+        let source = InMemoryDataSource::new()
+            .with_chain_head(1_100)
+            .with_indexed_block(1_000);
+        let health = readiness(&source, &HealthSettings::default()).await;
+        assert_eq!(health.status, HealthStatus::Degraded);
+        assert_eq!(health.dependencies[1].status, DependencyStatus::Degraded);
+    }
+
+    #[rocket::async_test]
+    async fn test_unreachable_dependency_is_unavailable() {
+        let source = InMemoryDataSource::new().with_rpc_down();
+        let health = readiness(&source, &HealthSettings::default()).await;
+        assert_eq!(health.status, HealthStatus::Unavailable);
+        let rpc = &health.dependencies[0];
+        assert_eq!(rpc.status, DependencyStatus::Down);
+        assert_eq!(
+            rpc.error.as_deref(),
+            Some("upstream data source unavailable")
+        );
+        assert_eq!(health.dependencies[1].block_lag, None);
+    }
+
+    #[rocket::async_test]
+    async fn test_readiness_is_cached() {
+        let cache = ReadinessCache::default();
+        let settings = HealthSettings::default();
+        let metrics = Metrics::default();
+        let up = InMemoryDataSource::new();
+        let down = InMemoryDataSource::new().with_rpc_down();
+        let health = cached_readiness(&cache, &up, &settings, &metrics).await;
+        assert_eq!(health.status, HealthStatus::Ok);
+        let health = cached_readiness(&cache, &down, &settings, &metrics).await;
+        assert_eq!(health.status, HealthStatus::Ok);
+
+        let uncached = HealthSettings {
+            cache_ms: 0,
+            ..settings
+        };
+        let health = cached_readiness(&cache, &down, &uncached, &metrics).await;
+        assert_eq!(health.status, HealthStatus::Unavailable);
+    }
+
+    #[rocket::async_test]
+    async fn test_slow_probe_times_out() {
+        let slow = async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(1)
+        };
+        let probe = probe(Duration::from_millis(10), slow).await;
+        assert_eq!(probe.result, Err("timed out after 10ms".to_string()));
+        assert!(probe.latency < Duration::from_secs(5));
+    }
+}
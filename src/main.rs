@@ -1,18 +1,37 @@
 #[macro_use]
 extern crate rocket;
 
+mod access_log;
+mod auth;
+mod cache;
+mod config;
+mod cors;
+mod data_source;
+mod decimal;
 mod error;
+mod health;
+mod metrics;
+mod orderbook;
+mod rate_limit;
 mod routes;
+mod strategy;
+mod swap;
+mod token_registry;
 mod types;
+mod validation;
 
-use rocket_cors::{AllowedHeaders, AllowedMethods, AllowedOrigins, CorsOptions};
-use utoipa::OpenApi;
+use utoipa::openapi::extensions::Extensions;
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
 use utoipa_swagger_ui::SwaggerUi;
 
 #[derive(OpenApi)]
 #[openapi(
     paths(
         routes::health::get_health,
+        routes::health::get_health_live,
+        routes::health::get_health_ready,
+        routes::version::get_version,
         routes::tokens::get_tokens,
         routes::swap::post_swap_quote,
         routes::swap::post_swap_calldata,
@@ -26,6 +45,7 @@ use utoipa_swagger_ui::SwaggerUi;
         routes::trades::get_trades_by_address,
     ),
     components(),
+    modifiers(&SecurityAddon, &BuildInfoAddon),
     tags(
         (name = "Health", description = "Health check endpoints"),
         (name = "Tokens", description = "Token information endpoints"),
@@ -42,28 +62,46 @@ use utoipa_swagger_ui::SwaggerUi;
 )]
 struct ApiDoc;
 
-fn configure_cors() -> CorsOptions {
-    let allowed_methods: AllowedMethods = ["Get", "Post", "Options"]
-        .iter()
-        .map(|s| std::str::FromStr::from_str(s).unwrap())
-        .collect();
+/// Documents the two ways to present an API key. Scopes listed on each
+/// operation are the key scopes it requires.
+struct SecurityAddon;
 
-    CorsOptions {
-        allowed_origins: AllowedOrigins::all(),
-        allowed_methods,
-        allowed_headers: AllowedHeaders::all(),
-        allow_credentials: false,
-        ..Default::default()
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-API-Key"))),
+        );
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
     }
 }
 
-fn rocket() -> rocket::Rocket<rocket::Build> {
-    let cors = configure_cors()
-        .to_cors()
-        .expect("CORS configuration failed");
+/// Records the commit in the document's `info`, so the served spec
+/// identifies the build behind it.
+struct BuildInfoAddon;
+
+impl Modify for BuildInfoAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        openapi.info.version = env!("CARGO_PKG_VERSION").into();
+        let commit = Extensions::builder()
+            .add("x-commit-sha", routes::version::COMMIT_SHA)
+            .build();
+        openapi
+            .info
+            .extensions
+            .get_or_insert_with(Default::default)
+            .merge(commit);
+    }
+}
 
-    rocket::build()
+fn rocket() -> rocket::Rocket<rocket::Build> {
+    rocket::custom(config::figment())
         .mount("/", routes::health::routes())
+        .mount("/", routes::version::routes())
         .mount("/v1/tokens", routes::tokens::routes())
         .mount("/v1/swap", routes::swap::routes())
         .mount("/v1/order", routes::order::routes())
@@ -73,7 +111,17 @@ fn rocket() -> rocket::Rocket<rocket::Build> {
             "/",
             SwaggerUi::new("/swagger/<tail..>").url("/api-doc/openapi.json", ApiDoc::openapi()),
         )
-        .attach(cors)
+        .register("/", error::catchers())
+        .manage(health::ReadinessCache::default())
+        .attach(access_log::fairing())
+        .attach(config::fairing())
+        .attach(metrics::fairing())
+        .attach(cors::fairing())
+        .attach(auth::fairing())
+        .attach(rate_limit::fairing())
+        .attach(rate_limit::headers())
+        .attach(token_registry::fairing())
+        .attach(data_source::fairing())
 }
 
 #[launch]
@@ -84,9 +132,13 @@ fn launch() -> _ {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rocket::http::Header;
     use rocket::http::Status;
     use rocket::local::blocking::Client;
 
+    /// The local key configured in Rocket.toml's debug profile.
+    const DEV_AUTHORIZATION: &str = "Bearer st0x-dev-key";
+
     fn client() -> Client {
         Client::tracked(rocket()).expect("valid rocket instance")
     }
@@ -100,4 +152,173 @@ mod tests {
             serde_json::from_str(&response.into_string().unwrap()).unwrap();
         assert_eq!(body["status"], "ok");
     }
+
+    #[test]
+    fn test_tokens_endpoint_serves_configured_registry() {
+        let client = client();
+        let response = client.get("/v1/tokens").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let body: serde_json::Value =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        assert!(body["tokens"].is_array());
+    }
+
+    fn assert_json_error(response: rocket::local::blocking::LocalResponse<'_>, status: Status) {
+        assert_eq!(response.status(), status);
+        assert_eq!(
+            response.content_type(),
+            Some(rocket::http::ContentType::JSON)
+        );
+        let body: serde_json::Value =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        assert!(body["error"]["code"].is_string());
+        assert!(body["error"]["message"].is_string());
+    }
+
+    #[test]
+    fn test_malformed_path_params_return_json_errors() {
+        let client = client();
+        for path in [
+            "/v1/order/not-a-hash",
+            "/v1/orders/tx/0x1234",
+            "/v1/orders/not-an-address",
+            "/v1/trades/tx/0xZZ",
+            "/v1/trades/0x833589",
+        ] {
+            assert_json_error(client.get(path).dispatch(), Status::UnprocessableEntity);
+        }
+    }
+
+    #[test]
+    fn test_malformed_bodies_return_json_errors() {
+        let client = client();
+        for path in [
+            "/v1/swap/quote",
+            "/v1/swap/calldata",
+            "/v1/order/dca",
+            "/v1/order/solver",
+            "/v1/order/cancel",
+        ] {
+            for body in ["", "{", "{}", r#"{"inputToken": "not-an-address"}"#] {
+                let response = client
+                    .post(path)
+                    .header(rocket::http::ContentType::JSON)
+                    .header(Header::new("Authorization", DEV_AUTHORIZATION))
+                    .body(body)
+                    .dispatch();
+                assert_json_error(response, Status::BadRequest);
+            }
+        }
+    }
+
+    #[test]
+    fn test_protected_routes_require_api_key() {
+        let client = client();
+        let order_hash = format!("/v1/order/0x{}", "ab".repeat(32));
+        assert_json_error(client.get(order_hash).dispatch(), Status::Unauthorized);
+        for path in ["/v1/swap/quote", "/v1/order/dca", "/v1/order/cancel"] {
+            let response = client
+                .post(path)
+                .header(rocket::http::ContentType::JSON)
+                .header(Header::new("X-API-Key", "not-a-key"))
+                .body("{}")
+                .dispatch();
+            assert_json_error(response, Status::Unauthorized);
+        }
+    }
+
+    #[test]
+    fn test_openapi_documents_security_schemes() {
+        let doc = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let schemes = &doc["components"]["securitySchemes"];
+        assert_eq!(schemes["api_key"]["name"], "X-API-Key");
+        assert_eq!(schemes["bearer"]["scheme"], "bearer");
+        let security = &doc["paths"]["/v1/order/dca"]["post"]["security"];
+        assert_eq!(security[0]["api_key"], serde_json::json!(["deploy"]));
+        assert!(doc["paths"]["/v1/tokens"]["get"].get("security").is_none());
+    }
+
+    #[test]
+    fn test_openapi_info_records_commit() {
+        let doc = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        assert_eq!(doc["info"]["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(doc["info"]["x-commit-sha"], routes::version::COMMIT_SHA);
+        assert!(doc["paths"]["/version"]["get"].is_object());
+    }
+
+    #[test]
+    fn test_version_endpoint() {
+        let client = client();
+        let response = client.get("/version").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let body: serde_json::Value =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(body["commitSha"], routes::version::COMMIT_SHA);
+        assert_eq!(
+            body["contracts"]["orderbook"],
+            "0x5fbdb2315678afecb367f032d93f642f64180aa3"
+        );
+    }
+
+    #[test]
+    fn test_metrics_endpoint() {
+        let client = client();
+        assert_eq!(client.get("/health").dispatch().status(), Status::Ok);
+        let response = client
+            .get("/metrics")
+            .header(Header::new("Authorization", DEV_AUTHORIZATION))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let text = response.into_string().unwrap();
+        assert!(text.contains(
+            "st0x_http_requests_total{method=\"GET\",route=\"/health\",status=\"200\"} 1"
+        ));
+    }
+
+    #[test]
+    fn test_unknown_route_returns_json_error() {
+        let client = client();
+        assert_json_error(client.get("/v1/nope").dispatch(), Status::NotFound);
+    }
+
+    #[test]
+    fn test_responses_carry_request_id() {
+        let client = client();
+        let response = client.get("/health").dispatch();
+        assert!(response.headers().get_one("X-Request-Id").is_some());
+
+        let response = client
+            .get("/v1/order/0x01")
+            .header(Header::new("X-Request-Id", "trace-7"))
+            .dispatch();
+        assert_eq!(response.headers().get_one("X-Request-Id"), Some("trace-7"));
+        let body: serde_json::Value =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(body["error"]["requestId"], "trace-7");
+    }
+
+    #[test]
+    fn test_debug_cors_policy_allows_local_origins() {
+        let client = client();
+        let response = client
+            .req(rocket::http::Method::Options, "/v1/tokens")
+            .header(Header::new("Origin", "http://localhost:5173"))
+            .header(Header::new("Access-Control-Request-Method", "GET"))
+            .dispatch();
+        assert_eq!(response.status(), Status::NoContent);
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            Some("http://localhost:5173")
+        );
+        assert_eq!(
+            response.headers().get_one("Access-Control-Max-Age"),
+            Some("3600")
+        );
+
+        let response = client
+            .get("/v1/tokens")
+            .header(Header::new("Origin", "https://example.com"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Forbidden);
+    }
 }
@@ -0,0 +1,498 @@
+//! Prometheus metrics.
+//!
+//! The fairing times every request and counts responses per route and
+//! status; the data source records its upstream calls, both caches record
+//! hits and misses, and the swap routes record how quotes were filled. Metrics are served at `/metrics` in the
+//! Prometheus text format: on the API port to keys with the admin scope, or
+//! without a key on a separate listener when `metrics.admin_port` is set.
+
+use crate::auth::{AdminScope, Authorized};
+use crate::config::AppConfig;
+use crate::error::ApiError;
+use rocket::fairing::{self, Fairing, Info, Kind};
+use rocket::http::{ContentType, Method, Status};
+use rocket::{Build, Config, Data, Orbit, Request, Response, Rocket, Route, State};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Upper bounds, in seconds, of the latency histogram buckets.
+const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetricsSettings {
+    /// Serve `/metrics` on this port instead of the API port.
+    #[serde(default)]
+    pub admin_port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Histogram {
+    /// Cumulative counts per bucket of [`LATENCY_BUCKETS`].
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    /// By method, route and status.
+    requests: BTreeMap<(String, String, u16), u64>,
+    /// By method and route.
+    request_latency: BTreeMap<(String, String), Histogram>,
+    /// By upstream and outcome.
+    upstream_requests: BTreeMap<(&'static str, &'static str), u64>,
+    upstream_latency: BTreeMap<&'static str, Histogram>,
+    /// By endpoint and outcome.
+    swap_quotes: BTreeMap<(&'static str, &'static str), u64>,
+    /// By cache and outcome.
+    cache_lookups: BTreeMap<(&'static str, &'static str), u64>,
+}
+
+#[derive(Debug, Default)]
+pub struct Metrics {
+    registry: Mutex<Registry>,
+}
+
+impl Metrics {
+    fn registry(&self) -> std::sync::MutexGuard<'_, Registry> {
+        self.registry.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn record_request(&self, method: Method, route: &str, status: Status, elapsed: Duration) {
+        let mut registry = self.registry();
+        let method = method.as_str().to_string();
+        *registry
+            .requests
+            .entry((method.clone(), route.to_string(), status.code))
+            .or_default() += 1;
+        registry
+            .request_latency
+            .entry((method, route.to_string()))
+            .or_default()
+            .observe(elapsed);
+    }
+
+    /// Times a call to `upstream`, counting it as an error if it fails.
+    pub async fn observe_upstream<T, E>(
+        &self,
+        upstream: &'static str,
+        call: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, E> {
+        let started = Instant::now();
+        let result = call.await;
+        let outcome = if result.is_ok() { "ok" } else { "error" };
+        let mut registry = self.registry();
+        *registry
+            .upstream_requests
+            .entry((upstream, outcome))
+            .or_default() += 1;
+        registry
+            .upstream_latency
+            .entry(upstream)
+            .or_default()
+            .observe(started.elapsed());
+        result
+    }
+
+    /// Counts a swap route attempt by whether the requested output could be
+    /// filled.
+    pub fn record_swap_quote<T>(&self, endpoint: &'static str, result: &Result<T, ApiError>) {
+        let outcome = match result {
+            Ok(_) => "filled",
+            Err(ApiError::InsufficientLiquidity(_)) => "insufficient_liquidity",
+            Err(_) => "error",
+        };
+        *self
+            .registry()
+            .swap_quotes
+            .entry((endpoint, outcome))
+            .or_default() += 1;
+    }
+
+    /// Counts a lookup in `cache` as a hit or a miss.
+    pub fn record_cache_lookup(&self, cache: &'static str, hit: bool) {
+        let outcome = if hit { "hit" } else { "miss" };
+        *self
+            .registry()
+            .cache_lookups
+            .entry((cache, outcome))
+            .or_default() += 1;
+    }
+
+    /// Renders every metric in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let registry = self.registry();
+        let mut out = String::new();
+
+        header(
+            &mut out,
+            "st0x_http_requests_total",
+            "counter",
+            "HTTP requests by method, route and status.",
+        );
+        for ((method, route, status), count) in &registry.requests {
+            let labels = labels(&[
+                ("method", method),
+                ("route", route),
+                ("status", &status.to_string()),
+            ]);
+            let _ = writeln!(out, "st0x_http_requests_total{{{labels}}} {count}");
+        }
+
+        header(
+            &mut out,
+            "st0x_http_request_duration_seconds",
+            "histogram",
+            "HTTP request latency by method and route.",
+        );
+        for ((method, route), histogram) in &registry.request_latency {
+            let labels = labels(&[("method", method), ("route", route)]);
+            write_histogram(
+                &mut out,
+                "st0x_http_request_duration_seconds",
+                &labels,
+                histogram,
+            );
+        }
+
+        header(
+            &mut out,
+            "st0x_upstream_requests_total",
+            "counter",
+            "Calls to the subgraph and RPC node by outcome.",
+        );
+        for ((upstream, outcome), count) in &registry.upstream_requests {
+            let labels = labels(&[("upstream", upstream), ("outcome", outcome)]);
+            let _ = writeln!(out, "st0x_upstream_requests_total{{{labels}}} {count}");
+        }
+
+        header(
+            &mut out,
+            "st0x_upstream_request_duration_seconds",
+            "histogram",
+            "Latency of calls to the subgraph and RPC node.",
+        );
+        for (upstream, histogram) in &registry.upstream_latency {
+            let labels = labels(&[("upstream", upstream)]);
+            write_histogram(
+                &mut out,
+                "st0x_upstream_request_duration_seconds",
+                &labels,
+                histogram,
+            );
+        }
+
+        header(
+            &mut out,
+            "st0x_swap_quotes_total",
+            "counter",
+            "Swap routing attempts by endpoint and whether the output was filled.",
+        );
+        for ((endpoint, outcome), count) in &registry.swap_quotes {
+            let labels = labels(&[("endpoint", endpoint), ("outcome", outcome)]);
+            let _ = writeln!(out, "st0x_swap_quotes_total{{{labels}}} {count}");
+        }
+
+        header(
+            &mut out,
+            "st0x_cache_lookups_total",
+            "counter",
+            "Cache lookups by cache and whether they hit.",
+        );
+        for ((cache, outcome), count) in &registry.cache_lookups {
+            let labels = labels(&[("cache", cache), ("outcome", outcome)]);
+            let _ = writeln!(out, "st0x_cache_lookups_total{{{labels}}} {count}");
+        }
+        out
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn labels(pairs: &[(&str, &str)]) -> String {
+    pairs
+        .iter()
+        .map(|(name, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{name}=\"{value}\"")
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn write_histogram(out: &mut String, name: &str, labels: &str, histogram: &Histogram) {
+    for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+        let _ = writeln!(out, "{name}_bucket{{{labels},le=\"{bound}\"}} {count}");
+    }
+    let count = histogram.count;
+    let _ = writeln!(out, "{name}_bucket{{{labels},le=\"+Inf\"}} {count}");
+    let _ = writeln!(out, "{name}_sum{{{labels}}} {}", histogram.sum);
+    let _ = writeln!(out, "{name}_count{{{labels}}} {count}");
+}
+
+/// `/metrics` on the API port, which anyone can reach.
+#[get("/metrics")]
+pub async fn get_metrics(
+    auth: Result<Authorized<AdminScope>, ApiError>,
+    metrics: &State<Arc<Metrics>>,
+) -> Result<(ContentType, String), ApiError> {
+    auth?;
+    Ok((ContentType::Plain, metrics.render()))
+}
+
+/// `/metrics` on the admin listener, which only the operator can reach.
+#[get("/metrics")]
+async fn get_admin_metrics(metrics: &State<Arc<Metrics>>) -> (ContentType, String) {
+    (ContentType::Plain, metrics.render())
+}
+
+pub fn routes() -> Vec<Route> {
+    rocket::routes![get_metrics]
+}
+
+/// The admin listener serving `metrics`, when `settings` asks for one. It
+/// shares only the API's address and limits, so none of the application
+/// settings (API keys among them) are loaded into it.
+fn admin_server(
+    api: &Config,
+    settings: &MetricsSettings,
+    metrics: Arc<Metrics>,
+) -> Option<Rocket<Build>> {
+    let config = Config {
+        address: api.address,
+        port: settings.admin_port?,
+        limits: api.limits.clone(),
+        ..Config::default()
+    };
+    Some(
+        rocket::custom(config)
+            .mount("/", rocket::routes![get_admin_metrics])
+            .manage(metrics),
+    )
+}
+
+/// When the request reached the metrics fairing.
+struct RequestStart(Instant);
+
+/// Manages [`Metrics`], records every request and serves `/metrics`.
+/// Attach after the configuration fairing and before the data source.
+pub struct MetricsFairing;
+
+pub fn fairing() -> MetricsFairing {
+    MetricsFairing
+}
+
+#[rocket::async_trait]
+impl Fairing for MetricsFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Metrics",
+            kind: Kind::Ignite | Kind::Liftoff | Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        let Some(config) = rocket.state::<AppConfig>() else {
+            error!("metrics need the configuration fairing");
+            return Err(rocket);
+        };
+        let admin_port = config.metrics.admin_port;
+        let api_port = rocket.figment().extract_inner::<u16>("port").ok();
+        if admin_port.is_some() && admin_port == api_port {
+            error!("metrics.admin_port must differ from the API port");
+            return Err(rocket);
+        }
+        let rocket = rocket.manage(Arc::new(Metrics::default()));
+        Ok(match admin_port {
+            Some(_) => rocket,
+            None => rocket.mount("/", routes()),
+        })
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<Orbit>) {
+        let (Some(config), Some(metrics)) =
+            (rocket.state::<AppConfig>(), rocket.state::<Arc<Metrics>>())
+        else {
+            return;
+        };
+        let Some(admin) = admin_server(rocket.config(), &config.metrics, metrics.clone()) else {
+            return;
+        };
+        rocket::tokio::spawn(async move {
+            if let Err(e) = admin.launch().await {
+                error!("metrics admin server failed: {}", e);
+            }
+        });
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _: &mut Data<'_>) {
+        req.local_cache(|| RequestStart(Instant::now()));
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let Some(metrics) = req.rocket().state::<Arc<Metrics>>() else {
+            return;
+        };
+        let RequestStart(started) = req.local_cache(|| RequestStart(Instant::now()));
+        // Route templates keep the label set small; unmatched paths share one.
+        let route = req.route().map_or("unmatched", |route| route.uri.as_str());
+        metrics.record_request(req.method(), route, res.status(), started.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source::memory;
+    use rocket::http::Header;
+    use rocket::local::blocking::Client;
+
+    #[get("/orders/<id>")]
+    fn order(id: u32) -> String {
+        id.to_string()
+    }
+
+    fn api(settings: MetricsSettings) -> Rocket<Build> {
+        rocket::build()
+            .mount("/v1", rocket::routes![order])
+            .register("/", crate::error::catchers())
+            .manage(memory::api_keys())
+            .manage(AppConfig {
+                metrics: settings,
+                ..memory::config()
+            })
+            .attach(fairing())
+    }
+
+    fn serves_metrics<P>(rocket: &Rocket<P>) -> bool
+    where
+        P: rocket::Phase,
+    {
+        rocket.routes().any(|route| route.uri.path() == "/metrics")
+    }
+
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        let mut histogram = Histogram::default();
+        histogram.observe(Duration::from_millis(20));
+        histogram.observe(Duration::from_secs(20));
+        assert_eq!(histogram.buckets[..3], [0, 0, 1]);
+        assert_eq!(histogram.buckets[LATENCY_BUCKETS.len() - 1], 1);
+        assert_eq!(histogram.count, 2);
+    }
+
+    #[rocket::async_test]
+    async fn test_render_upstream_swap_and_cache_metrics() {
+        let metrics = Metrics::default();
+        let ok: Result<(), ()> = metrics.observe_upstream("rpc", async { Ok(()) }).await;
+        assert!(ok.is_ok());
+        metrics.record_swap_quote::<()>(
+            "quote",
+            &Err(ApiError::InsufficientLiquidity("only 1 of 2".into())),
+        );
+        metrics.record_cache_lookup("readiness", true);
+        metrics.record_cache_lookup("readiness", false);
+        metrics.record_cache_lookup("readiness", true);
+        let text = metrics.render();
+        assert!(text.contains("st0x_upstream_requests_total{upstream=\"rpc\",outcome=\"ok\"} 1"));
+        assert!(text.contains("st0x_upstream_request_duration_seconds_count{upstream=\"rpc\"} 1"));
+        assert!(text.contains(
+            "st0x_swap_quotes_total{endpoint=\"quote\",outcome=\"insufficient_liquidity\"} 1"
+        ));
+        assert!(text.contains("st0x_cache_lookups_total{cache=\"readiness\",outcome=\"hit\"} 2"));
+        assert!(text.contains("st0x_cache_lookups_total{cache=\"readiness\",outcome=\"miss\"} 1"));
+        assert!(text.contains("# TYPE st0x_http_request_duration_seconds histogram"));
+    }
+
+    #[test]
+    fn test_requests_are_counted_per_route_and_status() {
+        let client = Client::tracked(api(MetricsSettings::default())).unwrap();
+        assert_eq!(client.get("/v1/orders/1").dispatch().status(), Status::Ok);
+        assert_eq!(client.get("/v1/orders/2").dispatch().status(), Status::Ok);
+        assert_eq!(client.get("/nope").dispatch().status(), Status::NotFound);
+
+        let response = client
+            .get("/metrics")
+            .header(Header::new("X-API-Key", memory::API_KEY))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::Plain));
+        let text = response.into_string().unwrap();
+        assert!(text.contains(
+            "st0x_http_requests_total{method=\"GET\",route=\"/v1/orders/<id>\",status=\"200\"} 2"
+        ));
+        assert!(text.contains(
+            "st0x_http_requests_total{method=\"GET\",route=\"unmatched\",status=\"404\"} 1"
+        ));
+        assert!(text.contains(
+            "st0x_http_request_duration_seconds_bucket{method=\"GET\",route=\"/v1/orders/<id>\",le=\"+Inf\"} 2"
+        ));
+    }
+
+    #[test]
+    fn test_api_metrics_need_the_admin_scope() {
+        let client = Client::tracked(api(MetricsSettings::default())).unwrap();
+        let response = client.get("/metrics").dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+        let response = client
+            .get("/metrics")
+            .header(Header::new("X-API-Key", memory::PARTNER_API_KEY))
+            .dispatch();
+        assert_eq!(response.status(), Status::Forbidden);
+    }
+
+    #[rocket::async_test]
+    async fn test_admin_port_moves_metrics_off_the_api() {
+        let rocket = api(MetricsSettings::default()).ignite().await.unwrap();
+        assert!(serves_metrics(&rocket));
+
+        let settings = MetricsSettings {
+            admin_port: Some(9100),
+        };
+        let rocket = api(settings.clone()).ignite().await.unwrap();
+        assert!(!serves_metrics(&rocket));
+
+        let metrics = rocket.state::<Arc<Metrics>>().unwrap().clone();
+        let admin = admin_server(rocket.config(), &settings, metrics).unwrap();
+        assert!(serves_metrics(&admin));
+        assert_eq!(admin.figment().extract_inner::<u16>("port").unwrap(), 9100);
+        for secret in ["api_keys", "rpc_url"] {
+            assert!(rocket.figment().find_value(secret).is_ok());
+            assert!(admin.figment().find_value(secret).is_err());
+        }
+        let client = rocket::local::asynchronous::Client::untracked(admin)
+            .await
+            .unwrap();
+        let response = client.get("/metrics").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+
+        let metrics = Arc::new(Metrics::default());
+        let unset = MetricsSettings::default();
+        assert!(admin_server(rocket.config(), &unset, metrics).is_none());
+    }
+}
@@ -0,0 +1,130 @@
+//! Solidity bindings for the Rain orderbook (v4) contracts this API talks to,
+//! and helpers for encoding the calls integrators have to send.
+
+use crate::types::common::{Amount, Approval, TokenRef};
+use alloy::primitives::{Address, U256};
+use alloy::sol_types::SolCall;
+
+alloy::sol! {
+    #[derive(Debug)]
+    struct IO {
+        address token;
+        uint8 decimals;
+        uint256 vaultId;
+    }
+
+    #[derive(Debug)]
+    struct EvaluableV3 {
+        address interpreter;
+        address store;
+        bytes bytecode;
+    }
+
+    #[derive(Debug)]
+    struct OrderV3 {
+        address owner;
+        EvaluableV3 evaluable;
+        IO[] validInputs;
+        IO[] validOutputs;
+        bytes32 nonce;
+    }
+
+    #[derive(Debug)]
+    struct SignedContextV1 {
+        address signer;
+        uint256[] context;
+        bytes signature;
+    }
+
+    #[derive(Debug)]
+    struct OrderConfigV3 {
+        EvaluableV3 evaluable;
+        IO[] validInputs;
+        IO[] validOutputs;
+        bytes32 nonce;
+        bytes32 secret;
+        bytes meta;
+    }
+
+    #[derive(Debug)]
+    struct TaskV1 {
+        EvaluableV3 evaluable;
+        SignedContextV1[] signedContext;
+    }
+
+    #[derive(Debug)]
+    struct TakeOrderConfigV3 {
+        OrderV3 order;
+        uint256 inputIOIndex;
+        uint256 outputIOIndex;
+        SignedContextV1[] signedContext;
+    }
+
+    #[derive(Debug)]
+    struct TakeOrdersConfigV3 {
+        uint256 minimumInput;
+        uint256 maximumInput;
+        uint256 maximumIORatio;
+        TakeOrderConfigV3[] orders;
+        bytes data;
+    }
+
+    #[derive(Debug)]
+    struct Quote {
+        OrderV3 order;
+        uint256 inputIOIndex;
+        uint256 outputIOIndex;
+        SignedContextV1[] signedContext;
+    }
+
+    interface IOrderBookV4 {
+        function quote(Quote calldata quoteConfig)
+            external
+            view
+            returns (bool exists, uint256 outputMax, uint256 ioRatio);
+
+        function takeOrders2(TakeOrdersConfigV3 calldata config)
+            external
+            returns (uint256 totalTakerInput, uint256 totalTakerOutput);
+
+        function addOrder2(OrderConfigV3 calldata config, TaskV1[] calldata post)
+            external
+            returns (bool stateChanged);
+
+        function removeOrder2(OrderV3 calldata order, TaskV1[] calldata tasks)
+            external
+            returns (bool stateChanged);
+
+        function deposit2(address token, uint256 vaultId, uint256 amount, TaskV1[] calldata tasks)
+            external;
+
+        function withdraw2(
+            address token,
+            uint256 vaultId,
+            uint256 targetAmount,
+            TaskV1[] calldata tasks
+        ) external;
+
+        function multicall(bytes[] calldata data) external returns (bytes[] memory results);
+    }
+
+    interface IParserV2 {
+        function parse2(bytes memory data) external view returns (bytes memory bytecode);
+    }
+
+    interface IERC20 {
+        function approve(address spender, uint256 amount) external returns (bool);
+    }
+}
+
+/// The ERC-20 approval letting `spender` pull `amount` of `token`.
+pub fn approval(token: &TokenRef, spender: Address, amount: U256) -> Approval {
+    Approval {
+        token: token.address,
+        spender,
+        amount: Amount(amount),
+        amount_formatted: Amount(amount).formatted(token.decimals),
+        symbol: token.symbol.clone(),
+        approval_data: IERC20::approveCall { spender, amount }.abi_encode().into(),
+    }
+}
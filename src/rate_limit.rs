@@ -0,0 +1,375 @@
+//! Token-bucket rate limiting.
+//!
+//! Every protected route belongs to the group named by the scope it requires.
+//! Requests with a valid API key draw from a bucket per key and group, sized
+//! by the key's tier; anything else draws from a bucket per client IP using
+//! the `anonymous` tier. Limits live in the `rate_limits` setting, keyed by
+//! tier and then group; a group without a limit is not rate limited.
+//!
+//! The client IP is the peer address unless Rocket's `ip_header` names a
+//! header set by a trusted proxy. Requests without an IP share one bucket.
+
+use crate::auth::Scope;
+use crate::config::AppConfig;
+use crate::error::ApiError;
+use alloy::primitives::B256;
+use rocket::fairing::AdHoc;
+use rocket::http::Header;
+use rocket::Request;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Tier applied to requests without a valid API key.
+pub const ANONYMOUS_TIER: &str = "anonymous";
+/// Most buckets tracked at once.
+const MAX_BUCKETS: usize = 10_000;
+/// Least recently used buckets dropped together when a new client arrives at
+/// the cap, so a flood of new clients pays for one scan per batch rather than
+/// one per request.
+const EVICTION_BATCH: usize = MAX_BUCKETS / 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Limit {
+    /// Requests allowed in a burst.
+    pub capacity: u32,
+    /// Requests regained per minute.
+    pub per_minute: u32,
+}
+
+impl Limit {
+    fn refill_per_second(&self) -> f64 {
+        f64::from(self.per_minute) / 60.0
+    }
+
+    /// Seconds for a bucket holding `tokens` to reach `target`.
+    fn seconds_until(&self, tokens: f64, target: f64) -> u64 {
+        if tokens >= target {
+            return 0;
+        }
+        if self.per_minute == 0 {
+            return u64::MAX;
+        }
+        ((target - tokens) / self.refill_per_second()).ceil() as u64
+    }
+}
+
+/// Tier name to per-group limits.
+pub type Tiers = HashMap<String, HashMap<Scope, Limit>>;
+
+/// Who a bucket belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Client {
+    Key(B256),
+    Ip(Option<IpAddr>),
+}
+
+/// The outcome of a rate-limit check, reported in `X-RateLimit-*` headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    pub limit: u32,
+    pub remaining: u32,
+    /// Seconds until the bucket is full again.
+    pub reset: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    tiers: Tiers,
+    buckets: Mutex<HashMap<(Client, Scope), Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(tiers: Tiers) -> Self {
+        Self {
+            tiers,
+            buckets: Mutex::default(),
+        }
+    }
+
+    /// Takes a token from `client`'s bucket for `group`. `Ok(None)` means the
+    /// group is not rate limited for `tier`.
+    pub fn check(
+        &self,
+        client: Client,
+        tier: &str,
+        group: Scope,
+        now: Instant,
+    ) -> Result<Option<RateLimitStatus>, (RateLimitStatus, ApiError)> {
+        let Some(limit) = self.tiers.get(tier).and_then(|groups| groups.get(&group)) else {
+            return Ok(None);
+        };
+        let capacity = f64::from(limit.capacity);
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() >= MAX_BUCKETS && !buckets.contains_key(&(client, group)) {
+            evict_least_recent(&mut buckets);
+        }
+        let bucket = buckets.entry((client, group)).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * limit.refill_per_second()).min(capacity);
+        bucket.updated = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+        let status = RateLimitStatus {
+            limit: limit.capacity,
+            remaining: bucket.tokens.floor() as u32,
+            reset: limit.seconds_until(bucket.tokens, capacity),
+        };
+        if allowed {
+            return Ok(Some(status));
+        }
+        let retry_after = limit.seconds_until(bucket.tokens, 1.0).max(1);
+        Err((
+            status,
+            ApiError::TooManyRequests {
+                message: format!("rate limit of {} {group} requests exceeded", limit.capacity),
+                retry_after,
+            },
+        ))
+    }
+
+    /// Checks `req` and records the status for [`headers`] to report.
+    pub fn check_request(
+        &self,
+        req: &Request<'_>,
+        client: Client,
+        tier: &str,
+        group: Scope,
+    ) -> Result<(), ApiError> {
+        let (status, result) = match self.check(client, tier, group, Instant::now()) {
+            Ok(status) => (status, Ok(())),
+            Err((status, e)) => (Some(status), Err(e)),
+        };
+        req.local_cache(|| CachedStatus(status));
+        result
+    }
+}
+
+/// Drops the [`EVICTION_BATCH`] least recently used buckets. An evicted
+/// client starts again from a full bucket.
+fn evict_least_recent(buckets: &mut HashMap<(Client, Scope), Bucket>) {
+    let mut updated: Vec<Instant> = buckets.values().map(|bucket| bucket.updated).collect();
+    let (_, &mut cutoff, _) = updated.select_nth_unstable(EVICTION_BATCH - 1);
+    buckets.retain(|_, bucket| bucket.updated > cutoff);
+}
+
+struct CachedStatus(Option<RateLimitStatus>);
+
+pub fn fairing() -> AdHoc {
+    AdHoc::try_on_ignite("Rate limits", |rocket| async move {
+        let Some(config) = rocket.state::<AppConfig>() else {
+            error!("rate limits need the configuration fairing");
+            return Err(rocket);
+        };
+        let limiter = RateLimiter::new(config.rate_limits.clone());
+        Ok(rocket.manage(limiter))
+    })
+}
+
+/// Adds `X-RateLimit-*` headers to responses of rate-limited requests.
+pub fn headers() -> AdHoc {
+    AdHoc::on_response("Rate limit headers", |req, res| {
+        Box::pin(async move {
+            let CachedStatus(status) = req.local_cache(|| CachedStatus(None));
+            if let Some(status) = status {
+                res.set_header(Header::new("X-RateLimit-Limit", status.limit.to_string()));
+                res.set_header(Header::new(
+                    "X-RateLimit-Remaining",
+                    status.remaining.to_string(),
+                ));
+                res.set_header(Header::new("X-RateLimit-Reset", status.reset.to_string()));
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{ApiKeyEntry, ApiKeyStore, Authorized, QuoteScope};
+    use rocket::http::Status;
+    use std::time::Duration;
+
+    fn limiter(capacity: u32, per_minute: u32) -> RateLimiter {
+        let groups = HashMap::from([(
+            Scope::Quote,
+            Limit {
+                capacity,
+                per_minute,
+            },
+        )]);
+        RateLimiter::new(HashMap::from([("standard".to_string(), groups)]))
+    }
+
+    const KEY: Client = Client::Key(B256::ZERO);
+
+    #[test]
+    fn test_bucket_allows_burst_then_limits() {
+        let limiter = limiter(2, 60);
+        let now = Instant::now();
+        let first = limiter.check(KEY, "standard", Scope::Quote, now).unwrap();
+        assert_eq!(
+            first,
+            Some(RateLimitStatus {
+                limit: 2,
+                remaining: 1,
+                reset: 1
+            })
+        );
+        let second = limiter.check(KEY, "standard", Scope::Quote, now).unwrap();
+        assert_eq!(second.unwrap().remaining, 0);
+        let (status, error) = limiter
+            .check(KEY, "standard", Scope::Quote, now)
+            .unwrap_err();
+        assert_eq!(status.remaining, 0);
+        assert_eq!(status.reset, 2);
+        assert!(matches!(
+            error,
+            ApiError::TooManyRequests { retry_after: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_bucket_refills_over_time() {
+        let limiter = limiter(1, 30);
+        let now = Instant::now();
+        assert!(limiter.check(KEY, "standard", Scope::Quote, now).is_ok());
+        let (_, error) = limiter
+            .check(KEY, "standard", Scope::Quote, now)
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            ApiError::TooManyRequests { retry_after: 2, .. }
+        ));
+        let later = now + Duration::from_secs(2);
+        assert!(limiter.check(KEY, "standard", Scope::Quote, later).is_ok());
+    }
+
+    #[test]
+    fn test_buckets_are_per_client_and_group() {
+        let limiter = limiter(1, 1);
+        let now = Instant::now();
+        assert!(limiter.check(KEY, "standard", Scope::Quote, now).is_ok());
+        assert!(limiter.check(KEY, "standard", Scope::Quote, now).is_err());
+        let other = Client::Ip(Some(IpAddr::from([127, 0, 0, 1])));
+        assert!(limiter.check(other, "standard", Scope::Quote, now).is_ok());
+    }
+
+    #[test]
+    fn test_bucket_count_is_capped() {
+        let limiter = limiter(1, 1);
+        let start = Instant::now();
+        let ip = |i: usize| Client::Ip(Some(IpAddr::from((i as u32).to_be_bytes())));
+        for i in 0..MAX_BUCKETS {
+            let now = start + Duration::from_millis(i as u64);
+            assert!(limiter.check(ip(i), "standard", Scope::Quote, now).is_ok());
+        }
+        let now = start + Duration::from_millis(MAX_BUCKETS as u64);
+        // Known clients never trigger eviction.
+        assert!(limiter.check(ip(0), "standard", Scope::Quote, now).is_err());
+        assert_eq!(limiter.buckets.lock().unwrap().len(), MAX_BUCKETS);
+
+        assert!(limiter
+            .check(ip(MAX_BUCKETS), "standard", Scope::Quote, now)
+            .is_ok());
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(buckets.len(), MAX_BUCKETS - EVICTION_BATCH + 1);
+        // The least recently used go first; the rest keep their state.
+        assert!(!buckets.contains_key(&(ip(1), Scope::Quote)));
+        assert!(buckets.contains_key(&(ip(0), Scope::Quote)));
+        assert!(buckets.contains_key(&(ip(EVICTION_BATCH + 1), Scope::Quote)));
+    }
+
+    #[test]
+    fn test_unconfigured_groups_are_unlimited() {
+        let limiter = limiter(1, 1);
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert_eq!(
+                limiter.check(KEY, "standard", Scope::Read, now).unwrap(),
+                None
+            );
+            assert_eq!(
+                limiter.check(KEY, "premium", Scope::Quote, now).unwrap(),
+                None
+            );
+        }
+    }
+
+    #[get("/quote")]
+    fn quote(auth: Result<Authorized<QuoteScope>, ApiError>) -> Result<&'static str, ApiError> {
+        auth?;
+        Ok("ok")
+    }
+
+    fn client() -> rocket::local::blocking::Client {
+        let store = ApiKeyStore::new(vec![ApiKeyEntry {
+            name: "partner".into(),
+            key_hash: ApiKeyStore::hash_key("key"),
+            scopes: vec![Scope::Quote],
+            tier: "standard".into(),
+        }])
+        .unwrap();
+        let rocket = rocket::build()
+            .mount("/", rocket::routes![quote])
+            .register("/", crate::error::catchers())
+            .manage(store)
+            .manage(limiter(1, 1))
+            .attach(headers());
+        rocket::local::blocking::Client::tracked(rocket).expect("valid rocket instance")
+    }
+
+    #[test]
+    fn test_limited_requests_get_429_with_headers() {
+        let client = client();
+        let response = client
+            .get("/quote")
+            .header(Header::new("X-API-Key", "key"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.headers().get_one("X-RateLimit-Limit"), Some("1"));
+        assert_eq!(
+            response.headers().get_one("X-RateLimit-Remaining"),
+            Some("0")
+        );
+        assert_eq!(response.headers().get_one("X-RateLimit-Reset"), Some("60"));
+
+        let response = client
+            .get("/quote")
+            .header(Header::new("X-API-Key", "key"))
+            .dispatch();
+        assert_eq!(response.status(), Status::TooManyRequests);
+        assert_eq!(response.headers().get_one("Retry-After"), Some("60"));
+        assert_eq!(
+            response.headers().get_one("X-RateLimit-Remaining"),
+            Some("0")
+        );
+        let body: serde_json::Value =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(body["error"]["code"], "TOO_MANY_REQUESTS");
+        assert_eq!(body["error"]["details"]["retryAfterSeconds"], 60);
+    }
+
+    #[test]
+    fn test_requests_without_a_tier_limit_have_no_headers() {
+        let client = client();
+        // Keyless requests fall in the unconfigured anonymous tier.
+        let response = client.get("/quote").dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+        assert!(response.headers().get_one("X-RateLimit-Limit").is_none());
+    }
+}
@@ -1,22 +1,123 @@
-use crate::types::health::HealthResponse;
+use crate::config::AppConfig;
+use crate::data_source::OrderbookDataSource;
+use crate::health;
+use crate::metrics::Metrics;
+use crate::types::health::{HealthResponse, HealthStatus};
+use rocket::http::Status;
 use rocket::serde::json::Json;
-use rocket::Route;
+use rocket::{Route, State};
+use std::sync::Arc;
+
+fn live() -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: HealthStatus::Ok,
+        dependencies: Vec::new(),
+    })
+}
 
 #[utoipa::path(
     get,
     path = "/health",
     tag = "Health",
     responses(
-        (status = 200, description = "Service is healthy", body = HealthResponse),
+        (status = 200, description = "Service is running; same as /health/live", body = HealthResponse),
     )
 )]
 #[get("/health")]
 pub async fn get_health() -> Json<HealthResponse> {
-    Json(HealthResponse {
-        status: "ok".into(),
-    })
+    live()
+}
+
+#[utoipa::path(
+    get,
+    path = "/health/live",
+    tag = "Health",
+    responses(
+        (status = 200, description = "Service is running", body = HealthResponse),
+    )
+)]
+#[get("/health/live")]
+pub async fn get_health_live() -> Json<HealthResponse> {
+    live()
+}
+
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    tag = "Health",
+    responses(
+        (status = 200, description = "Every upstream is reachable; the index may be lagging", body = HealthResponse),
+        (status = 503, description = "A critical upstream is down", body = HealthResponse),
+    )
+)]
+#[get("/health/ready")]
+pub async fn get_health_ready(
+    data_source: &State<Box<dyn OrderbookDataSource>>,
+    config: &State<AppConfig>,
+    cache: &State<health::ReadinessCache>,
+    metrics: &State<Arc<Metrics>>,
+) -> (Status, Json<HealthResponse>) {
+    let response =
+        health::cached_readiness(cache, data_source.as_ref(), &config.health, metrics).await;
+    let status = match response.status {
+        HealthStatus::Unavailable => Status::ServiceUnavailable,
+        HealthStatus::Ok | HealthStatus::Degraded => Status::Ok,
+    };
+    (status, Json(response))
 }
 
 pub fn routes() -> Vec<Route> {
-    rocket::routes![get_health]
+    rocket::routes![get_health, get_health_live, get_health_ready]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source::memory::{self, InMemoryDataSource};
+
+    fn get(source: InMemoryDataSource, path: &str) -> (Status, serde_json::Value) {
+        let client = memory::client("/", routes(), source);
+        let response = client.get(path).dispatch();
+        let status = response.status();
+        let body = serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        (status, body)
+    }
+
+    #[test]
+    fn test_live_does_not_probe_upstreams() {
+        let source = InMemoryDataSource::new()
+            .with_rpc_down()
+            .with_subgraph_down();
+        let (status, body) = get(source, "/health/live");
+        assert_eq!(status, Status::Ok);
+        assert_eq!(body, serde_json::json!({ "status": "ok" }));
+    }
+
+    #[test]
+    fn test_ready_reports_dependencies() {
+        let source = InMemoryDataSource::new()
+            .with_chain_head(1_002)
+            .with_indexed_block(1_000);
+        let (status, body) = get(source, "/health/ready");
+        assert_eq!(status, Status::Ok);
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["dependencies"][0]["name"], "rpc");
+        assert_eq!(body["dependencies"][0]["status"], "up");
+        assert_eq!(body["dependencies"][1]["blockNumber"], 1_000);
+        assert_eq!(body["dependencies"][1]["blockLag"], 2);
+        assert!(body["dependencies"][1]["latencyMs"].is_u64());
+    }
+
+    #[test]
+    fn test_ready_is_unavailable_when_subgraph_is_down() {
+        let source = InMemoryDataSource::new().with_subgraph_down();
+        let (status, body) = get(source, "/health/ready");
+        assert_eq!(status, Status::ServiceUnavailable);
+        assert_eq!(body["status"], "unavailable");
+        assert_eq!(body["dependencies"][1]["status"], "down");
+        assert_eq!(
+            body["dependencies"][1]["error"],
+            "upstream data source unavailable"
+        );
+    }
 }
@@ -1,29 +1,68 @@
+use crate::auth::{Authorized, DeployScope, ReadScope};
+use crate::config::AppConfig;
+use crate::data_source::{OrderQuote, OrderRecord, OrderbookDataSource, VaultRecord};
 use crate::error::{ApiError, ApiErrorResponse};
-use crate::types::common::ValidatedFixedBytes;
+use crate::orderbook::{IOrderBookV4, OrderV3};
+use crate::strategy::dca::DcaStrategy;
+use crate::strategy::solver::SolverStrategy;
+use crate::strategy::{self, OrderSpec};
+use crate::token_registry::TokenRegistry;
+use crate::types::common::{Amount, ValidatedFixedBytes};
 use crate::types::order::{
-    CancelOrderRequest, CancelOrderResponse, DeployDcaOrderRequest, DeployOrderResponse,
-    DeploySolverOrderRequest, OrderDetail,
+    CancelOrderRequest, CancelOrderResponse, CancelSummary, CancelTransaction,
+    DeployDcaOrderRequest, DeployOrderResponse, DeploySolverOrderRequest, OrderDetail,
+    OrderDetailsInfo, OrderTradeEntry, TokenReturn,
 };
-use rocket::serde::json::Json;
-use rocket::Route;
+use alloy::primitives::U256;
+use alloy::sol_types::{SolCall, SolValue};
+use rocket::serde::json::{Error as JsonError, Json};
+use rocket::{Route, State};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 #[utoipa::path(
     post,
     path = "/v1/order/dca",
     tag = "Order",
     request_body = DeployDcaOrderRequest,
+    security(("api_key" = ["deploy"]), ("bearer" = ["deploy"])),
     responses(
         (status = 200, description = "DCA order deployment result", body = DeployOrderResponse),
-        (status = 400, description = "Bad request", body = ApiErrorResponse),
+        (status = 400, description = "Malformed request body", body = ApiErrorResponse),
+        (status = 401, description = "Missing or invalid API key", body = ApiErrorResponse),
+        (status = 403, description = "API key lacks the required scope", body = ApiErrorResponse),
+        (status = 422, description = "Invalid request fields", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit exceeded", body = ApiErrorResponse),
         (status = 500, description = "Internal server error", body = ApiErrorResponse),
+        (status = 503, description = "Upstream data source unavailable", body = ApiErrorResponse),
     )
 )]
 #[post("/dca", data = "<request>")]
 pub async fn post_order_dca(
-    request: Json<DeployDcaOrderRequest>,
+    auth: Result<Authorized<DeployScope>, ApiError>,
+    request: Result<Json<DeployDcaOrderRequest>, JsonError<'_>>,
+    registry: &State<TokenRegistry>,
+    config: &State<AppConfig>,
+    data_source: &State<Box<dyn OrderbookDataSource>>,
 ) -> Result<Json<DeployOrderResponse>, ApiError> {
-    let _ = request.into_inner();
-    todo!()
+    auth?;
+    let request = request?.into_inner();
+    let start_time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .as_secs();
+    let order = DcaStrategy::from_request(&request, registry, start_time)?;
+    let spec = OrderSpec {
+        rainlang: order
+            .strategy
+            .rainlang(&order.output_token, config.deployment.sub_parser),
+        input_token: order.input_token,
+        output_token: order.output_token,
+        input_vault_id: strategy::vault_id_or_random(request.input_vault_id),
+        output_vault_id: strategy::vault_id_or_random(request.output_vault_id),
+        deposit: order.strategy.budget,
+    };
+    let response = strategy::deploy_order(data_source.as_ref(), &config.deployment, spec).await?;
+    Ok(Json(response))
 }
 
 #[utoipa::path(
@@ -31,18 +70,39 @@ pub async fn post_order_dca(
     path = "/v1/order/solver",
     tag = "Order",
     request_body = DeploySolverOrderRequest,
+    security(("api_key" = ["deploy"]), ("bearer" = ["deploy"])),
     responses(
         (status = 200, description = "Solver order deployment result", body = DeployOrderResponse),
-        (status = 400, description = "Bad request", body = ApiErrorResponse),
+        (status = 400, description = "Malformed request body", body = ApiErrorResponse),
+        (status = 401, description = "Missing or invalid API key", body = ApiErrorResponse),
+        (status = 403, description = "API key lacks the required scope", body = ApiErrorResponse),
+        (status = 422, description = "Invalid request fields", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit exceeded", body = ApiErrorResponse),
         (status = 500, description = "Internal server error", body = ApiErrorResponse),
+        (status = 503, description = "Upstream data source unavailable", body = ApiErrorResponse),
     )
 )]
 #[post("/solver", data = "<request>")]
 pub async fn post_order_solver(
-    request: Json<DeploySolverOrderRequest>,
+    auth: Result<Authorized<DeployScope>, ApiError>,
+    request: Result<Json<DeploySolverOrderRequest>, JsonError<'_>>,
+    registry: &State<TokenRegistry>,
+    config: &State<AppConfig>,
+    data_source: &State<Box<dyn OrderbookDataSource>>,
 ) -> Result<Json<DeployOrderResponse>, ApiError> {
-    let _ = request.into_inner();
-    todo!()
+    auth?;
+    let request = request?.into_inner();
+    let order = SolverStrategy::from_request(&request, registry)?;
+    let spec = OrderSpec {
+        rainlang: order.strategy.rainlang(),
+        input_token: order.input_token,
+        output_token: order.output_token,
+        input_vault_id: strategy::vault_id_or_random(request.input_vault_id),
+        output_vault_id: strategy::vault_id_or_random(request.output_vault_id),
+        deposit: order.strategy.amount,
+    };
+    let response = strategy::deploy_order(data_source.as_ref(), &config.deployment, spec).await?;
+    Ok(Json(response))
 }
 
 #[utoipa::path(
@@ -52,16 +112,73 @@ pub async fn post_order_solver(
     params(
         ("order_hash" = String, Path, description = "The order hash"),
     ),
+    security(("api_key" = ["read"]), ("bearer" = ["read"])),
     responses(
         (status = 200, description = "Order details", body = OrderDetail),
+        (status = 401, description = "Missing or invalid API key", body = ApiErrorResponse),
+        (status = 403, description = "API key lacks the required scope", body = ApiErrorResponse),
         (status = 404, description = "Order not found", body = ApiErrorResponse),
+        (status = 422, description = "Malformed path or query parameter", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit exceeded", body = ApiErrorResponse),
         (status = 500, description = "Internal server error", body = ApiErrorResponse),
+        (status = 503, description = "Upstream data source unavailable", body = ApiErrorResponse),
     )
 )]
 #[get("/<order_hash>")]
-pub async fn get_order(order_hash: ValidatedFixedBytes) -> Result<Json<OrderDetail>, ApiError> {
-    let _ = order_hash;
-    todo!()
+pub async fn get_order(
+    auth: Result<Authorized<ReadScope>, ApiError>,
+    order_hash: ValidatedFixedBytes,
+    data_source: &State<Box<dyn OrderbookDataSource>>,
+) -> Result<Json<OrderDetail>, ApiError> {
+    auth?;
+    let Some(order) = data_source.order_by_hash(order_hash.0).await? else {
+        return Err(ApiError::NotFound(format!(
+            "order {} not found",
+            order_hash.0
+        )));
+    };
+    let quote = data_source.quote(&order).await?;
+    let io_ratio = OrderQuote::io_ratio_or_zero(quote.as_ref());
+    let trades = data_source
+        .trades_by_order(order.order_hash)
+        .await?
+        .into_iter()
+        .map(|trade| OrderTradeEntry {
+            id: trade.id,
+            tx_hash: trade.transaction.tx_hash,
+            input_amount: Amount(trade.input.amount),
+            input_amount_formatted: Amount(trade.input.amount)
+                .formatted(trade.input.token.decimals),
+            output_amount: Amount(trade.output.amount),
+            output_amount_formatted: Amount(trade.output.amount)
+                .formatted(trade.output.token.decimals),
+            timestamp: trade.transaction.timestamp,
+            sender: trade.sender,
+        })
+        .collect();
+
+    Ok(Json(OrderDetail {
+        order_hash: order.order_hash,
+        owner: order.owner,
+        order_details: OrderDetailsInfo {
+            type_: order.order_type,
+            io_ratio,
+        },
+        input_vault_id: order.input.vault_id,
+        output_vault_id: order.output.vault_id,
+        input_vault_balance: Amount(order.input.balance),
+        input_vault_balance_formatted: Amount(order.input.balance)
+            .formatted(order.input.token.decimals),
+        output_vault_balance: Amount(order.output.balance),
+        output_vault_balance_formatted: Amount(order.output.balance)
+            .formatted(order.output.token.decimals),
+        input_token: order.input.token,
+        output_token: order.output.token,
+        io_ratio,
+        created_at: order.timestamp_added,
+        orderbook_id: order.orderbook,
+        trades,
+    }))
 }
 
 #[utoipa::path(
@@ -69,19 +186,106 @@ pub async fn get_order(order_hash: ValidatedFixedBytes) -> Result<Json<OrderDeta
     path = "/v1/order/cancel",
     tag = "Order",
     request_body = CancelOrderRequest,
+    security(("api_key" = ["deploy"]), ("bearer" = ["deploy"])),
     responses(
         (status = 200, description = "Cancel order result", body = CancelOrderResponse),
         (status = 400, description = "Bad request", body = ApiErrorResponse),
+        (status = 401, description = "Missing or invalid API key", body = ApiErrorResponse),
+        (status = 403, description = "API key lacks the required scope", body = ApiErrorResponse),
         (status = 404, description = "Order not found", body = ApiErrorResponse),
+        (status = 409, description = "Order already cancelled", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit exceeded", body = ApiErrorResponse),
         (status = 500, description = "Internal server error", body = ApiErrorResponse),
+        (status = 503, description = "Upstream data source unavailable", body = ApiErrorResponse),
     )
 )]
 #[post("/cancel", data = "<request>")]
 pub async fn post_order_cancel(
-    request: Json<CancelOrderRequest>,
+    auth: Result<Authorized<DeployScope>, ApiError>,
+    request: Result<Json<CancelOrderRequest>, JsonError<'_>>,
+    data_source: &State<Box<dyn OrderbookDataSource>>,
 ) -> Result<Json<CancelOrderResponse>, ApiError> {
-    let _ = request.into_inner();
-    todo!()
+    auth?;
+    let order_hash = request?.into_inner().order_hash;
+    let Some(order) = data_source.order_by_hash(order_hash).await? else {
+        return Err(ApiError::NotFound(format!("order {order_hash} not found")));
+    };
+    if !order.active {
+        return Err(ApiError::Conflict(format!(
+            "order {order_hash} is already cancelled"
+        )));
+    }
+    let decoded = OrderV3::abi_decode(&order.order_bytes)
+        .map_err(|e| ApiError::Internal(format!("invalid order {order_hash}: {e}")))?;
+    let remove = IOrderBookV4::removeOrder2Call {
+        order: decoded,
+        tasks: vec![],
+    };
+    let mut transactions = vec![CancelTransaction {
+        to: order.orderbook,
+        data: remove.abi_encode().into(),
+        value: U256::ZERO,
+    }];
+    let mut withdrawn: Vec<&VaultRecord> = Vec::new();
+    for vault in [&order.input, &order.output] {
+        if vault.balance.is_zero() || vault_is_shared(data_source.as_ref(), &order, vault).await? {
+            continue;
+        }
+        let withdraw = IOrderBookV4::withdraw2Call {
+            token: vault.token.address,
+            vaultId: vault.vault_id,
+            targetAmount: vault.balance,
+            tasks: vec![],
+        };
+        transactions.push(CancelTransaction {
+            to: order.orderbook,
+            data: withdraw.abi_encode().into(),
+            value: U256::ZERO,
+        });
+        withdrawn.push(vault);
+    }
+    let mut tokens_returned: Vec<TokenReturn> = Vec::new();
+    for vault in &withdrawn {
+        let amount: U256 = withdrawn
+            .iter()
+            .filter(|other| other.token.address == vault.token.address)
+            .map(|other| other.balance)
+            .sum();
+        if tokens_returned
+            .iter()
+            .all(|returned| returned.token != vault.token.address)
+        {
+            tokens_returned.push(TokenReturn {
+                token: vault.token.address,
+                symbol: vault.token.symbol.clone(),
+                amount: Amount(amount),
+                amount_formatted: Amount(amount).formatted(vault.token.decimals),
+            });
+        }
+    }
+
+    Ok(Json(CancelOrderResponse {
+        transactions,
+        summary: CancelSummary {
+            vaults_to_withdraw: withdrawn.len() as u32,
+            tokens_returned,
+        },
+    }))
+}
+
+/// Whether another active order still relies on `vault`, in which case
+/// withdrawing it would drain that order too.
+async fn vault_is_shared(
+    data_source: &dyn OrderbookDataSource,
+    order: &OrderRecord,
+    vault: &VaultRecord,
+) -> Result<bool, ApiError> {
+    let orders = data_source
+        .active_orders_using_vault(order.owner, vault.token.address, vault.vault_id)
+        .await?;
+    Ok(orders
+        .iter()
+        .any(|other| other.order_hash != order.order_hash))
 }
 
 pub fn routes() -> Vec<Route> {
@@ -92,3 +296,506 @@ pub fn routes() -> Vec<Route> {
         post_order_cancel
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source::memory::{self, InMemoryDataSource, OWNER};
+    use crate::orderbook::{IOrderBookV4, IERC20};
+    use crate::types::order::OrderType;
+    use alloy::primitives::{Address, Bytes, B256, U256};
+    use alloy::sol_types::SolCall;
+    use rocket::http::{ContentType, Header, Status};
+    use serde_json::json;
+
+    #[test]
+    fn test_get_order_detail() {
+        let mut order = memory::order(0x11, OWNER, 100);
+        order.order_type = OrderType::Dca;
+        let source = InMemoryDataSource::new()
+            .with_order(order.clone())
+            .with_trade(memory::trade(&order, memory::tx(0x21, 200), 1_000_000, 500))
+            .with_trade(memory::trade(&order, memory::tx(0x22, 300), 2_000_000, 900))
+            .with_quote(
+                order.order_hash,
+                OrderQuote {
+                    io_ratio: U256::from(500_000_000_000_000u64),
+                    max_output: U256::from(1_000_000_000_000_000_000u128),
+                },
+            );
+        let client = memory::client("/v1/order", routes(), source);
+
+        let response = client
+            .get(format!("/v1/order/{}", order.order_hash))
+            .header(Header::new("X-API-Key", memory::API_KEY))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let body: serde_json::Value =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(body["orderDetails"]["type"], "dca");
+        assert_eq!(body["orderDetails"]["ioRatio"], "0.0005");
+        assert_eq!(body["ioRatio"], "0.0005");
+        assert_eq!(body["inputToken"]["symbol"], "USDC");
+        assert_eq!(body["inputVaultId"], "0x1");
+        assert_eq!(body["outputVaultId"], "0x2");
+        assert_eq!(body["inputVaultBalance"], "2000000");
+        assert_eq!(body["outputVaultBalance"], "1000000000000000000");
+        assert_eq!(body["inputVaultBalanceFormatted"], "2");
+        assert_eq!(body["outputVaultBalanceFormatted"], "1");
+        assert_eq!(body["createdAt"], 100);
+        assert_eq!(
+            body["orderbookId"]
+                .as_str()
+                .unwrap()
+                .parse::<Address>()
+                .unwrap(),
+            memory::ORDERBOOK
+        );
+        let trades = body["trades"].as_array().unwrap();
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0]["timestamp"], 300);
+        assert_eq!(trades[0]["inputAmount"], "2000000");
+        assert_eq!(trades[0]["inputAmountFormatted"], "2");
+        assert_eq!(trades[0]["sender"], memory::TAKER.to_string());
+        assert_eq!(trades[1]["timestamp"], 200);
+    }
+
+    #[test]
+    fn test_get_order_without_quote_reports_zero_ratio() {
+        let order = memory::order(0x11, OWNER, 100);
+        let client = memory::client(
+            "/v1/order",
+            routes(),
+            InMemoryDataSource::new().with_order(order.clone()),
+        );
+        let response = client
+            .get(format!("/v1/order/{}", order.order_hash))
+            .header(Header::new("X-API-Key", memory::API_KEY))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let body: serde_json::Value =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(body["ioRatio"], "0");
+        assert_eq!(body["orderDetails"]["type"], "solver");
+        assert!(body["trades"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get_order_unknown_hash_returns_404() {
+        let client = memory::client("/v1/order", routes(), InMemoryDataSource::new());
+        let response = client
+            .get(format!("/v1/order/{}", B256::repeat_byte(0x99)))
+            .header(Header::new("X-API-Key", memory::API_KEY))
+            .dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        let body: serde_json::Value =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(body["error"]["code"], "NOT_FOUND");
+    }
+
+    fn post_json(
+        client: &rocket::local::blocking::Client,
+        uri: &str,
+        body: serde_json::Value,
+    ) -> (Status, serde_json::Value) {
+        let response = client
+            .post(uri.to_string())
+            .header(ContentType::JSON)
+            .header(Header::new("X-API-Key", memory::API_KEY))
+            .body(body.to_string())
+            .dispatch();
+        let status = response.status();
+        let body = serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        (status, body)
+    }
+
+    /// Splits a deployment's multicall back into its `addOrder2` and
+    /// `deposit2` calls.
+    fn decode_deployment(
+        body: &serde_json::Value,
+    ) -> (IOrderBookV4::addOrder2Call, IOrderBookV4::deposit2Call) {
+        let data: Bytes = body["data"].as_str().unwrap().parse().unwrap();
+        let multicall = IOrderBookV4::multicallCall::abi_decode(&data).unwrap();
+        assert_eq!(multicall.data.len(), 2);
+        (
+            IOrderBookV4::addOrder2Call::abi_decode(&multicall.data[0]).unwrap(),
+            IOrderBookV4::deposit2Call::abi_decode(&multicall.data[1]).unwrap(),
+        )
+    }
+
+    fn dca_request() -> serde_json::Value {
+        json!({
+            "inputToken": memory::usdc().address,
+            "outputToken": memory::weth().address,
+            "budgetAmount": "1000000000000000000",
+            "period": 4,
+            "periodUnit": "hours",
+            "startIo": "3000",
+            "floorIo": "2500",
+        })
+    }
+
+    #[test]
+    fn test_deploy_dca_order() {
+        let client = memory::client("/v1/order", routes(), InMemoryDataSource::new());
+        let (status, body) = post_json(&client, "/v1/order/dca", dca_request());
+        assert_eq!(status, Status::Ok);
+        assert_eq!(
+            body["to"].as_str().unwrap().parse::<Address>().unwrap(),
+            memory::ORDERBOOK
+        );
+        assert_eq!(body["value"], "0x0");
+
+        let (add_order, deposit) = decode_deployment(&body);
+        let config = add_order.config;
+        let deployment = memory::deployment();
+        assert_eq!(config.evaluable.interpreter, deployment.interpreter);
+        assert_eq!(config.evaluable.store, deployment.store);
+        assert_eq!(config.validInputs[0].token, memory::usdc().address);
+        assert_eq!(config.validInputs[0].decimals, 6);
+        assert_eq!(config.validOutputs[0].token, memory::weth().address);
+        assert_eq!(config.validOutputs[0].decimals, 18);
+        let rainlang = String::from_utf8(config.evaluable.bytecode.to_vec()).unwrap();
+        assert!(rainlang.contains("period: 14400,"));
+        assert!(rainlang.contains("start-io: 3000000000000000000000,"));
+        assert!(rainlang.contains("floor-io: 2500000000000000000000,"));
+        assert!(rainlang.contains(&format!("using-words-from {}", deployment.sub_parser)));
+        assert_eq!(
+            strategy::order_type_from_meta(Some(&config.meta)),
+            OrderType::Dca
+        );
+
+        assert_eq!(deposit.token, memory::weth().address);
+        assert_eq!(deposit.vaultId, config.validOutputs[0].vaultId);
+        assert_eq!(deposit.amount, U256::from(1_000_000_000_000_000_000u128));
+
+        let approvals = body["approvals"].as_array().unwrap();
+        assert_eq!(approvals.len(), 1);
+        assert_eq!(approvals[0]["symbol"], "WETH");
+        assert_eq!(approvals[0]["amount"], "1000000000000000000");
+    }
+
+    #[test]
+    fn test_deploy_dca_order_vault_ids() {
+        let client = memory::client("/v1/order", routes(), InMemoryDataSource::new());
+        let mut request = dca_request();
+        request["inputVaultId"] = json!("0x7");
+        request["outputVaultId"] = json!("0x8");
+        let (_, body) = post_json(&client, "/v1/order/dca", request);
+        let (add_order, _) = decode_deployment(&body);
+        assert_eq!(add_order.config.validInputs[0].vaultId, U256::from(7));
+        assert_eq!(add_order.config.validOutputs[0].vaultId, U256::from(8));
+
+        // Fresh vaults are random per deployment.
+        let (_, first) = post_json(&client, "/v1/order/dca", dca_request());
+        let (_, second) = post_json(&client, "/v1/order/dca", dca_request());
+        let (first, _) = decode_deployment(&first);
+        let (second, _) = decode_deployment(&second);
+        assert_ne!(
+            first.config.validInputs[0].vaultId,
+            second.config.validInputs[0].vaultId
+        );
+        assert_ne!(
+            first.config.validInputs[0].vaultId,
+            first.config.validOutputs[0].vaultId
+        );
+        assert_ne!(first.config.nonce, second.config.nonce);
+    }
+
+    #[test]
+    fn test_deploy_dca_order_lists_every_violation() {
+        let client = memory::client("/v1/order", routes(), InMemoryDataSource::new());
+        let mut request = dca_request();
+        request["period"] = json!(0);
+        request["floorIo"] = json!("3500");
+        request["budgetAmount"] = json!("0");
+        let (status, body) = post_json(&client, "/v1/order/dca", request);
+        assert_eq!(status, Status::UnprocessableEntity);
+        assert_eq!(body["error"]["code"], "UNPROCESSABLE_ENTITY");
+        let fields: Vec<_> = body["error"]["details"]["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| field["field"].as_str().unwrap())
+            .collect();
+        assert_eq!(fields, vec!["budgetAmount", "period", "floorIo"]);
+    }
+
+    #[test]
+    fn test_deploy_requires_deploy_scope() {
+        let order = memory::order(0x11, OWNER, 100);
+        let client = memory::client(
+            "/v1/order",
+            routes(),
+            InMemoryDataSource::new().with_order(order.clone()),
+        );
+        for (uri, body) in [
+            ("/v1/order/dca", dca_request()),
+            ("/v1/order/cancel", json!({ "orderHash": order.order_hash })),
+        ] {
+            let response = client
+                .post(uri)
+                .header(ContentType::JSON)
+                .header(Header::new("X-API-Key", memory::PARTNER_API_KEY))
+                .body(body.to_string())
+                .dispatch();
+            assert_eq!(response.status(), Status::Forbidden, "{uri}");
+        }
+        let response = client
+            .get(format!("/v1/order/{}", order.order_hash))
+            .header(Header::new("X-API-Key", memory::PARTNER_API_KEY))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+    }
+
+    #[test]
+    fn test_deploy_dca_order_rejects_malformed_amount() {
+        let client = memory::client("/v1/order", routes(), InMemoryDataSource::new());
+        let mut request = dca_request();
+        request["budgetAmount"] = json!("1.5");
+        let (status, body) = post_json(&client, "/v1/order/dca", request);
+        assert_eq!(status, Status::BadRequest);
+        let message = body["error"]["message"].as_str().unwrap();
+        assert!(message.starts_with("invalid request body"), "{message}");
+    }
+
+    #[test]
+    fn test_deploy_dca_order_rejects_unsupported_token() {
+        let client = memory::client("/v1/order", routes(), InMemoryDataSource::new());
+        let mut request = dca_request();
+        request["inputToken"] = json!(Address::repeat_byte(0xcc));
+        let (status, body) = post_json(&client, "/v1/order/dca", request);
+        assert_eq!(status, Status::UnprocessableEntity);
+        assert_eq!(body["error"]["details"]["fields"][0]["field"], "inputToken");
+        assert!(body["error"]["details"]["fields"][0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("not supported"));
+    }
+
+    /// Reads a `name: value` binding back out of a passed-through source.
+    fn rainlang_binding(rainlang: &str, name: &str) -> U256 {
+        let prefix = format!("{name}: ");
+        let line = rainlang
+            .lines()
+            .find_map(|line| line.strip_prefix(prefix.as_str()))
+            .unwrap();
+        line.trim_end_matches([',', ';']).parse().unwrap()
+    }
+
+    #[test]
+    fn test_deploy_solver_order_round_trip() {
+        let client = memory::client("/v1/order", routes(), InMemoryDataSource::new());
+        let (status, body) = post_json(
+            &client,
+            "/v1/order/solver",
+            json!({
+                "inputToken": memory::weth().address,
+                "outputToken": memory::usdc().address,
+                "amount": "2500000000",
+                "ioRatio": "0.0004",
+                "inputVaultId": "0x3",
+                "outputVaultId": "0x4",
+            }),
+        );
+        assert_eq!(status, Status::Ok);
+        assert_eq!(
+            body["to"].as_str().unwrap().parse::<Address>().unwrap(),
+            memory::ORDERBOOK
+        );
+
+        let (add_order, deposit) = decode_deployment(&body);
+        let config = add_order.config;
+        assert_eq!(config.validInputs.len(), 1);
+        assert_eq!(config.validInputs[0].token, memory::weth().address);
+        assert_eq!(config.validInputs[0].decimals, 18);
+        assert_eq!(config.validInputs[0].vaultId, U256::from(3));
+        assert_eq!(config.validOutputs.len(), 1);
+        assert_eq!(config.validOutputs[0].token, memory::usdc().address);
+        assert_eq!(config.validOutputs[0].decimals, 6);
+        assert_eq!(config.validOutputs[0].vaultId, U256::from(4));
+
+        let rainlang = String::from_utf8(config.evaluable.bytecode.to_vec()).unwrap();
+        assert!(rainlang.starts_with(strategy::SOLVER_MARKER));
+        let io_ratio = rainlang_binding(&rainlang, "io-ratio");
+        assert_eq!(crate::decimal::format_ratio(io_ratio), "0.0004");
+        assert_eq!(
+            strategy::order_type_from_meta(Some(&config.meta)),
+            OrderType::Solver
+        );
+
+        assert_eq!(deposit.token, memory::usdc().address);
+        assert_eq!(deposit.vaultId, U256::from(4));
+        assert_eq!(deposit.amount.to_string(), "2500000000");
+
+        let approvals = body["approvals"].as_array().unwrap();
+        assert_eq!(approvals.len(), 1);
+        assert_eq!(
+            approvals[0]["token"]
+                .as_str()
+                .unwrap()
+                .parse::<Address>()
+                .unwrap(),
+            memory::usdc().address
+        );
+        assert_eq!(approvals[0]["amount"], "2500000000");
+        let approval_data: Bytes = approvals[0]["approvalData"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let approve = IERC20::approveCall::abi_decode(&approval_data).unwrap();
+        assert_eq!(approve.spender, memory::ORDERBOOK);
+        assert_eq!(approve.amount, deposit.amount);
+    }
+
+    #[test]
+    fn test_deploy_dca_order_round_trips_parameters() {
+        let client = memory::client("/v1/order", routes(), InMemoryDataSource::new());
+        let (_, body) = post_json(&client, "/v1/order/dca", dca_request());
+        let (add_order, _) = decode_deployment(&body);
+        let rainlang = String::from_utf8(add_order.config.evaluable.bytecode.to_vec()).unwrap();
+        assert_eq!(rainlang_binding(&rainlang, "period"), U256::from(4 * 3600));
+        assert_eq!(
+            crate::decimal::format_ratio(rainlang_binding(&rainlang, "start-io")),
+            "3000"
+        );
+        assert_eq!(
+            crate::decimal::format_ratio(rainlang_binding(&rainlang, "floor-io")),
+            "2500"
+        );
+        assert_eq!(
+            rainlang_binding(&rainlang, "budget"),
+            U256::from(1_000_000_000_000_000_000u128)
+        );
+    }
+
+    #[test]
+    fn test_deploy_solver_order_rejects_invalid_amount() {
+        let client = memory::client("/v1/order", routes(), InMemoryDataSource::new());
+        let (status, _) = post_json(
+            &client,
+            "/v1/order/solver",
+            json!({
+                "inputToken": memory::weth().address,
+                "outputToken": memory::usdc().address,
+                "amount": "-5",
+                "ioRatio": "0.0004",
+            }),
+        );
+        assert_eq!(status, Status::BadRequest);
+    }
+
+    fn cancel(
+        client: &rocket::local::blocking::Client,
+        order_hash: B256,
+    ) -> (Status, serde_json::Value) {
+        post_json(
+            client,
+            "/v1/order/cancel",
+            json!({ "orderHash": order_hash }),
+        )
+    }
+
+    fn decode_transaction<C: SolCall>(body: &serde_json::Value, index: usize) -> C {
+        let transaction = &body["transactions"][index];
+        assert_eq!(
+            transaction["to"]
+                .as_str()
+                .unwrap()
+                .parse::<Address>()
+                .unwrap(),
+            memory::ORDERBOOK
+        );
+        let data: Bytes = transaction["data"].as_str().unwrap().parse().unwrap();
+        C::abi_decode(&data).unwrap()
+    }
+
+    #[test]
+    fn test_cancel_order_withdraws_both_vaults() {
+        let order = memory::order(0x11, OWNER, 100);
+        let client = memory::client(
+            "/v1/order",
+            routes(),
+            InMemoryDataSource::new().with_order(order.clone()),
+        );
+        let (status, body) = cancel(&client, order.order_hash);
+        assert_eq!(status, Status::Ok);
+        assert_eq!(body["transactions"].as_array().unwrap().len(), 3);
+
+        let remove: IOrderBookV4::removeOrder2Call = decode_transaction(&body, 0);
+        assert_eq!(remove.order.owner, OWNER);
+        assert_eq!(remove.order.nonce, B256::repeat_byte(0x11));
+
+        let withdraw: IOrderBookV4::withdraw2Call = decode_transaction(&body, 1);
+        assert_eq!(withdraw.token, memory::usdc().address);
+        assert_eq!(withdraw.vaultId, U256::from(1));
+        assert_eq!(withdraw.targetAmount, U256::from(2_000_000));
+        let withdraw: IOrderBookV4::withdraw2Call = decode_transaction(&body, 2);
+        assert_eq!(withdraw.token, memory::weth().address);
+        assert_eq!(
+            withdraw.targetAmount,
+            U256::from(1_000_000_000_000_000_000u128)
+        );
+
+        assert_eq!(body["summary"]["vaultsToWithdraw"], 2);
+        let returned = body["summary"]["tokensReturned"].as_array().unwrap();
+        assert_eq!(returned.len(), 2);
+        assert_eq!(returned[0]["symbol"], "USDC");
+        assert_eq!(returned[0]["amount"], "2000000");
+        assert_eq!(returned[0]["amountFormatted"], "2");
+        assert_eq!(returned[1]["symbol"], "WETH");
+        assert_eq!(returned[1]["amount"], "1000000000000000000");
+        assert_eq!(returned[1]["amountFormatted"], "1");
+    }
+
+    #[test]
+    fn test_cancel_order_skips_empty_and_shared_vaults() {
+        let mut order = memory::order(0x11, OWNER, 100);
+        order.input.balance = U256::ZERO;
+        // Another live order sells from the same WETH vault.
+        let sibling = memory::order(0x12, OWNER, 200);
+        let client = memory::client(
+            "/v1/order",
+            routes(),
+            InMemoryDataSource::new()
+                .with_order(order.clone())
+                .with_order(sibling),
+        );
+        let (status, body) = cancel(&client, order.order_hash);
+        assert_eq!(status, Status::Ok);
+        assert_eq!(body["transactions"].as_array().unwrap().len(), 1);
+        assert_eq!(body["summary"]["vaultsToWithdraw"], 0);
+        assert!(body["summary"]["tokensReturned"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_cancel_order_ignores_inactive_vault_sharers() {
+        let order = memory::order(0x11, OWNER, 100);
+        let mut cancelled = memory::order(0x12, OWNER, 200);
+        cancelled.active = false;
+        let client = memory::client(
+            "/v1/order",
+            routes(),
+            InMemoryDataSource::new()
+                .with_order(order.clone())
+                .with_order(cancelled.clone()),
+        );
+        let (_, body) = cancel(&client, order.order_hash);
+        assert_eq!(body["summary"]["vaultsToWithdraw"], 2);
+
+        let (status, body) = cancel(&client, cancelled.order_hash);
+        assert_eq!(status, Status::Conflict);
+        assert_eq!(body["error"]["code"], "CONFLICT");
+    }
+
+    #[test]
+    fn test_cancel_unknown_order_returns_404() {
+        let client = memory::client("/v1/order", routes(), InMemoryDataSource::new());
+        let (status, _) = cancel(&client, B256::repeat_byte(0x99));
+        assert_eq!(status, Status::NotFound);
+    }
+}
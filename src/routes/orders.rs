@@ -1,8 +1,31 @@
+use crate::auth::{Authorized, ReadScope};
+use crate::data_source::{
+    missing_transaction_error, quote_orders, OrderQuote, OrderRecord, OrderbookDataSource,
+    PageRequest,
+};
 use crate::error::{ApiError, ApiErrorResponse};
-use crate::types::common::{ValidatedAddress, ValidatedFixedBytes};
-use crate::types::orders::{OrdersByTxResponse, OrdersListResponse, OrdersPaginationParams};
+use crate::types::common::{Amount, ValidatedAddress, ValidatedFixedBytes};
+use crate::types::orders::{
+    OrderByTxEntry, OrderSummary, OrdersByTxResponse, OrdersListResponse, OrdersPagination,
+    OrdersPaginationParams,
+};
 use rocket::serde::json::Json;
-use rocket::Route;
+use rocket::{Route, State};
+
+fn order_summary(order: OrderRecord, quote: Option<&OrderQuote>) -> OrderSummary {
+    OrderSummary {
+        order_hash: order.order_hash,
+        owner: order.owner,
+        output_vault_balance: Amount(order.output.balance),
+        output_vault_balance_formatted: Amount(order.output.balance)
+            .formatted(order.output.token.decimals),
+        input_token: order.input.token,
+        output_token: order.output.token,
+        io_ratio: OrderQuote::io_ratio_or_zero(quote),
+        created_at: order.timestamp_added,
+        orderbook_id: order.orderbook,
+    }
+}
 
 #[utoipa::path(
     get,
@@ -11,19 +34,45 @@ use rocket::Route;
     params(
         ("tx_hash" = String, Path, description = "Transaction hash"),
     ),
+    security(("api_key" = ["read"]), ("bearer" = ["read"])),
     responses(
         (status = 200, description = "Orders from transaction", body = OrdersByTxResponse),
         (status = 202, description = "Transaction not yet indexed", body = ApiErrorResponse),
+        (status = 401, description = "Missing or invalid API key", body = ApiErrorResponse),
+        (status = 403, description = "API key lacks the required scope", body = ApiErrorResponse),
         (status = 404, description = "Transaction not found", body = ApiErrorResponse),
+        (status = 422, description = "Malformed path or query parameter", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit exceeded", body = ApiErrorResponse),
         (status = 500, description = "Internal server error", body = ApiErrorResponse),
+        (status = 503, description = "Upstream data source unavailable", body = ApiErrorResponse),
     )
 )]
 #[get("/tx/<tx_hash>")]
 pub async fn get_orders_by_tx(
+    auth: Result<Authorized<ReadScope>, ApiError>,
     tx_hash: ValidatedFixedBytes,
+    data_source: &State<Box<dyn OrderbookDataSource>>,
 ) -> Result<Json<OrdersByTxResponse>, ApiError> {
-    let _ = tx_hash;
-    todo!()
+    auth?;
+    let Some(result) = data_source.orders_by_tx(tx_hash.0).await? else {
+        return Err(missing_transaction_error(data_source.as_ref(), tx_hash.0).await);
+    };
+    Ok(Json(OrdersByTxResponse {
+        tx_hash: result.transaction.tx_hash,
+        block_number: result.transaction.block_number,
+        timestamp: result.transaction.timestamp,
+        orders: result
+            .orders
+            .into_iter()
+            .map(|order| OrderByTxEntry {
+                order_hash: order.order_hash,
+                owner: order.owner,
+                orderbook_id: order.orderbook,
+                input_token: order.input.token,
+                output_token: order.output.token,
+            })
+            .collect(),
+    }))
 }
 
 #[utoipa::path(
@@ -34,21 +83,242 @@ pub async fn get_orders_by_tx(
         ("address" = String, Path, description = "Owner address"),
         OrdersPaginationParams,
     ),
+    security(("api_key" = ["read"]), ("bearer" = ["read"])),
     responses(
         (status = 200, description = "Paginated list of orders", body = OrdersListResponse),
         (status = 400, description = "Bad request", body = ApiErrorResponse),
+        (status = 401, description = "Missing or invalid API key", body = ApiErrorResponse),
+        (status = 403, description = "API key lacks the required scope", body = ApiErrorResponse),
+        (status = 422, description = "Malformed path or query parameter", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit exceeded", body = ApiErrorResponse),
         (status = 500, description = "Internal server error", body = ApiErrorResponse),
+        (status = 503, description = "Upstream data source unavailable", body = ApiErrorResponse),
     )
 )]
 #[get("/<address>?<params..>", rank = 2)]
 pub async fn get_orders_by_address(
+    auth: Result<Authorized<ReadScope>, ApiError>,
     address: ValidatedAddress,
     params: OrdersPaginationParams,
+    data_source: &State<Box<dyn OrderbookDataSource>>,
 ) -> Result<Json<OrdersListResponse>, ApiError> {
-    let _ = (address, params);
-    todo!()
+    auth?;
+    let page = PageRequest::from_params(params.page, params.page_size)?;
+    let result = data_source.orders_by_owner(address.0, page).await?;
+    let total_pages = result.total_pages(page.page_size);
+
+    // A failed quote leaves its order unpriced rather than failing the page.
+    let orders = quote_orders(data_source.as_ref(), result.items)
+        .await
+        .into_iter()
+        .map(|(order, quote)| {
+            let quote = quote.unwrap_or_else(|e| {
+                warn!("quote for order {} failed: {}", order.order_hash, e);
+                None
+            });
+            order_summary(order, quote.as_ref())
+        })
+        .collect();
+
+    Ok(Json(OrdersListResponse {
+        orders,
+        pagination: OrdersPagination {
+            page: page.page,
+            page_size: page.page_size,
+            total_orders: result.total,
+            total_pages,
+            has_more: u64::from(page.page) < total_pages,
+        },
+    }))
 }
 
 pub fn routes() -> Vec<Route> {
     rocket::routes![get_orders_by_tx, get_orders_by_address]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source::memory::{self, InMemoryDataSource, OWNER};
+    use crate::data_source::ChainTransaction;
+    use alloy::primitives::{B256, U256};
+    use rocket::http::{Header, Status};
+
+    fn get_json(
+        client: &rocket::local::blocking::Client,
+        uri: &str,
+    ) -> (Status, serde_json::Value) {
+        let response = client
+            .get(uri.to_string())
+            .header(Header::new("X-API-Key", memory::API_KEY))
+            .dispatch();
+        let status = response.status();
+        let body = serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        (status, body)
+    }
+
+    #[test]
+    fn test_orders_by_tx() {
+        let order = memory::order(0x11, OWNER, 100);
+        let source = InMemoryDataSource::new().with_order_added_in(order, memory::tx(0x21, 100));
+        let client = memory::client("/v1/orders", routes(), source);
+
+        let uri = format!("/v1/orders/tx/{}", memory::tx(0x21, 100).tx_hash);
+        let (status, body) = get_json(&client, &uri);
+        assert_eq!(status, Status::Ok);
+        assert_eq!(body["blockNumber"], 1033);
+        assert_eq!(body["orders"].as_array().unwrap().len(), 1);
+        assert_eq!(body["orders"][0]["inputToken"]["symbol"], "USDC");
+        assert_eq!(body["orders"][0]["outputToken"]["symbol"], "WETH");
+    }
+
+    #[test]
+    fn test_orders_by_tx_unknown_returns_404() {
+        let client = memory::client("/v1/orders", routes(), InMemoryDataSource::new());
+        let uri = format!("/v1/orders/tx/{}", memory::tx(0x21, 100).tx_hash);
+        let (status, body) = get_json(&client, &uri);
+        assert_eq!(status, Status::NotFound);
+        assert_eq!(body["error"]["code"], "NOT_FOUND");
+    }
+
+    #[test]
+    fn test_orders_by_tx_not_yet_indexed_returns_202() {
+        let pending = B256::repeat_byte(0x31);
+        let mined = B256::repeat_byte(0x32);
+        let source = InMemoryDataSource::new()
+            .with_chain_tx(pending, ChainTransaction::Pending)
+            .with_chain_tx(mined, ChainTransaction::Mined { block_number: 501 })
+            .with_indexed_block(500);
+        let client = memory::client("/v1/orders", routes(), source);
+
+        for tx_hash in [pending, mined] {
+            let response = client
+                .get(format!("/v1/orders/tx/{tx_hash}"))
+                .header(Header::new("X-API-Key", memory::API_KEY))
+                .dispatch();
+            assert_eq!(response.status(), Status::Accepted);
+            assert_eq!(response.headers().get_one("Retry-After"), Some("5"));
+            let body: serde_json::Value =
+                serde_json::from_str(&response.into_string().unwrap()).unwrap();
+            assert_eq!(body["error"]["code"], "NOT_YET_INDEXED");
+        }
+    }
+
+    #[test]
+    fn test_orders_by_tx_indexed_without_orders_returns_404() {
+        let tx_hash = B256::repeat_byte(0x32);
+        let source = InMemoryDataSource::new()
+            .with_chain_tx(tx_hash, ChainTransaction::Mined { block_number: 500 })
+            .with_indexed_block(500);
+        let client = memory::client("/v1/orders", routes(), source);
+        let (status, _) = get_json(&client, &format!("/v1/orders/tx/{tx_hash}"));
+        assert_eq!(status, Status::NotFound);
+    }
+
+    #[test]
+    fn test_orders_by_address() {
+        let order = memory::order(0x11, OWNER, 100);
+        let source = InMemoryDataSource::new()
+            .with_order(order)
+            .with_order(memory::order(0x12, memory::TAKER, 100))
+            .with_quote(
+                B256::repeat_byte(0x11),
+                OrderQuote {
+                    io_ratio: U256::from(2_000_000_000_000_000_000_000u128),
+                    max_output: U256::from(1_000_000_000_000_000_000u128),
+                },
+            );
+        let client = memory::client("/v1/orders", routes(), source);
+
+        let (status, body) = get_json(&client, &format!("/v1/orders/{OWNER}"));
+        assert_eq!(status, Status::Ok);
+        let orders = body["orders"].as_array().unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0]["ioRatio"], "2000");
+        assert_eq!(orders[0]["outputVaultBalance"], "1000000000000000000");
+        assert_eq!(orders[0]["outputVaultBalanceFormatted"], "1");
+        assert_eq!(body["pagination"]["totalOrders"], 1);
+        assert_eq!(body["pagination"]["hasMore"], false);
+    }
+
+    #[test]
+    fn test_orders_by_address_survives_failing_quote() {
+        let source = InMemoryDataSource::new()
+            .with_order(memory::order(0x11, OWNER, 100))
+            .with_order(memory::order(0x12, OWNER, 200))
+            .with_quote(
+                B256::repeat_byte(0x11),
+                OrderQuote {
+                    io_ratio: U256::from(2_000_000_000_000_000_000_000u128),
+                    max_output: U256::from(1_000_000_000_000_000_000u128),
+                },
+            )
+            .with_quote_error(B256::repeat_byte(0x12));
+        let client = memory::client("/v1/orders", routes(), source);
+
+        let (status, body) = get_json(&client, &format!("/v1/orders/{OWNER}"));
+        assert_eq!(status, Status::Ok);
+        let ratios: Vec<_> = body["orders"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|order| order["ioRatio"].as_str().unwrap())
+            .collect();
+        assert_eq!(ratios, vec!["0", "2000"]);
+    }
+
+    fn owner_with_orders(count: u8) -> rocket::local::blocking::Client {
+        let source = (0..count).fold(InMemoryDataSource::new(), |source, i| {
+            source.with_order(memory::order(0x40 + i, OWNER, 1000 + u64::from(i)))
+        });
+        memory::client("/v1/orders", routes(), source)
+    }
+
+    #[test]
+    fn test_orders_by_address_defaults_and_sorting() {
+        let client = owner_with_orders(3);
+        let (status, body) = get_json(&client, &format!("/v1/orders/{OWNER}"));
+        assert_eq!(status, Status::Ok);
+        let created: Vec<_> = body["orders"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|order| order["createdAt"].as_u64().unwrap())
+            .collect();
+        assert_eq!(created, vec![1002, 1001, 1000]);
+        assert_eq!(body["pagination"]["page"], 1);
+        assert_eq!(body["pagination"]["pageSize"], 20);
+        assert_eq!(body["pagination"]["totalPages"], 1);
+    }
+
+    #[test]
+    fn test_orders_by_address_pagination_metadata() {
+        let client = owner_with_orders(5);
+
+        let (_, body) = get_json(&client, &format!("/v1/orders/{OWNER}?page=2&pageSize=2"));
+        assert_eq!(body["orders"].as_array().unwrap().len(), 2);
+        assert_eq!(body["orders"][0]["createdAt"], 1002);
+        assert_eq!(body["pagination"]["totalOrders"], 5);
+        assert_eq!(body["pagination"]["totalPages"], 3);
+        assert_eq!(body["pagination"]["hasMore"], true);
+
+        let (_, body) = get_json(&client, &format!("/v1/orders/{OWNER}?page=3&pageSize=2"));
+        assert_eq!(body["orders"].as_array().unwrap().len(), 1);
+        assert_eq!(body["pagination"]["hasMore"], false);
+
+        let (status, body) = get_json(&client, &format!("/v1/orders/{OWNER}?page=9&pageSize=2"));
+        assert_eq!(status, Status::Ok);
+        assert!(body["orders"].as_array().unwrap().is_empty());
+        assert_eq!(body["pagination"]["hasMore"], false);
+    }
+
+    #[test]
+    fn test_orders_by_address_rejects_invalid_pagination() {
+        let client = owner_with_orders(1);
+        for query in ["page=0", "pageSize=0", "pageSize=101", "pageSize=1000000"] {
+            let (status, body) = get_json(&client, &format!("/v1/orders/{OWNER}?{query}"));
+            assert_eq!(status, Status::BadRequest, "{query}");
+            assert_eq!(body["error"]["code"], "BAD_REQUEST");
+        }
+    }
+}
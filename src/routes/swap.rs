@@ -1,27 +1,84 @@
+use crate::auth::{Authorized, QuoteScope};
+use crate::data_source::OrderbookDataSource;
 use crate::error::{ApiError, ApiErrorResponse};
+use crate::metrics::Metrics;
+use crate::orderbook;
+use crate::swap;
+use crate::types::common::{Amount, Ratio};
 use crate::types::swap::{
     SwapCalldataRequest, SwapCalldataResponse, SwapQuoteRequest, SwapQuoteResponse,
 };
-use rocket::serde::json::Json;
-use rocket::Route;
+use alloy::primitives::{Address, U256};
+use rocket::serde::json::{Error as JsonError, Json};
+use rocket::{Route, State};
+use std::sync::Arc;
+
+fn check_swap(
+    input_token: Address,
+    output_token: Address,
+    output_amount: Amount,
+) -> Result<U256, ApiError> {
+    if input_token == output_token {
+        return Err(ApiError::BadRequest(
+            "inputToken and outputToken must differ".into(),
+        ));
+    }
+    if output_amount.0.is_zero() {
+        return Err(ApiError::BadRequest("outputAmount must be positive".into()));
+    }
+    Ok(output_amount.0)
+}
 
 #[utoipa::path(
     post,
     path = "/v1/swap/quote",
     tag = "Swap",
     request_body = SwapQuoteRequest,
+    security(("api_key" = ["quote"]), ("bearer" = ["quote"])),
     responses(
         (status = 200, description = "Swap quote", body = SwapQuoteResponse),
-        (status = 400, description = "Bad request", body = ApiErrorResponse),
+        (status = 400, description = "Bad request or insufficient liquidity", body = ApiErrorResponse),
+        (status = 401, description = "Missing or invalid API key", body = ApiErrorResponse),
+        (status = 403, description = "API key lacks the required scope", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit exceeded", body = ApiErrorResponse),
         (status = 500, description = "Internal server error", body = ApiErrorResponse),
+        (status = 503, description = "Upstream data source unavailable", body = ApiErrorResponse),
     )
 )]
 #[post("/quote", data = "<request>")]
 pub async fn post_swap_quote(
-    request: Json<SwapQuoteRequest>,
+    auth: Result<Authorized<QuoteScope>, ApiError>,
+    request: Result<Json<SwapQuoteRequest>, JsonError<'_>>,
+    data_source: &State<Box<dyn OrderbookDataSource>>,
+    metrics: &State<Arc<Metrics>>,
 ) -> Result<Json<SwapQuoteResponse>, ApiError> {
-    let _ = request.into_inner();
-    todo!()
+    auth?;
+    let request = request?.into_inner();
+    let output_amount = check_swap(
+        request.input_token,
+        request.output_token,
+        request.output_amount,
+    )?;
+    let route = swap::quote_route(
+        data_source.as_ref(),
+        request.input_token,
+        request.output_token,
+        output_amount,
+        None,
+    )
+    .await;
+    metrics.record_swap_quote("quote", &route);
+    let route = route?;
+    let (input_decimals, output_decimals) = route.decimals();
+    Ok(Json(SwapQuoteResponse {
+        input_token: request.input_token,
+        output_token: request.output_token,
+        output_amount: Amount(route.total_output),
+        output_amount_formatted: Amount(route.total_output).formatted(output_decimals),
+        estimated_input: Amount(route.total_input),
+        estimated_input_formatted: Amount(route.total_input).formatted(input_decimals),
+        estimated_io_ratio: Ratio(route.io_ratio),
+    }))
 }
 
 #[utoipa::path(
@@ -29,20 +86,349 @@ pub async fn post_swap_quote(
     path = "/v1/swap/calldata",
     tag = "Swap",
     request_body = SwapCalldataRequest,
+    security(("api_key" = ["quote"]), ("bearer" = ["quote"])),
     responses(
         (status = 200, description = "Swap calldata", body = SwapCalldataResponse),
-        (status = 400, description = "Bad request", body = ApiErrorResponse),
+        (status = 400, description = "Bad request or insufficient liquidity within maximumIoRatio", body = ApiErrorResponse),
+        (status = 401, description = "Missing or invalid API key", body = ApiErrorResponse),
+        (status = 403, description = "API key lacks the required scope", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit exceeded", body = ApiErrorResponse),
         (status = 500, description = "Internal server error", body = ApiErrorResponse),
+        (status = 503, description = "Upstream data source unavailable", body = ApiErrorResponse),
     )
 )]
 #[post("/calldata", data = "<request>")]
 pub async fn post_swap_calldata(
-    request: Json<SwapCalldataRequest>,
+    auth: Result<Authorized<QuoteScope>, ApiError>,
+    request: Result<Json<SwapCalldataRequest>, JsonError<'_>>,
+    data_source: &State<Box<dyn OrderbookDataSource>>,
+    metrics: &State<Arc<Metrics>>,
 ) -> Result<Json<SwapCalldataResponse>, ApiError> {
-    let _ = request.into_inner();
-    todo!()
+    auth?;
+    let request = request?.into_inner();
+    let output_amount = check_swap(
+        request.input_token,
+        request.output_token,
+        request.output_amount,
+    )?;
+    let maximum_io_ratio = request.maximum_io_ratio.0;
+    let route = swap::quote_route(
+        data_source.as_ref(),
+        request.input_token,
+        request.output_token,
+        output_amount,
+        Some(maximum_io_ratio),
+    )
+    .await;
+    metrics.record_swap_quote("calldata", &route);
+    let route = route?;
+    let (to, data) = swap::take_orders_call(&route, maximum_io_ratio)?;
+    let approval_amount = swap::maximum_input(&route, maximum_io_ratio)?;
+    // Every order in the route takes the same input token.
+    let approvals = route
+        .orders
+        .first()
+        .map(|order| orderbook::approval(&order.input.token, to, approval_amount))
+        .into_iter()
+        .collect();
+    Ok(Json(SwapCalldataResponse {
+        to,
+        data,
+        value: U256::ZERO,
+        estimated_input: Amount(route.total_input),
+        estimated_input_formatted: Amount(route.total_input).formatted(route.decimals().0),
+        approvals,
+    }))
 }
 
 pub fn routes() -> Vec<Route> {
     rocket::routes![post_swap_quote, post_swap_calldata]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source::memory::{self, InMemoryDataSource, OWNER};
+    use crate::data_source::OrderQuote;
+    use crate::orderbook::{IOrderBookV4, IERC20};
+    use alloy::primitives::{Bytes, B256};
+    use alloy::sol_types::SolCall;
+    use rocket::http::{ContentType, Header, Status};
+    use serde_json::json;
+
+    fn ether(value: u128) -> U256 {
+        U256::from(value) * U256::from(10).pow(U256::from(18))
+    }
+
+    fn post_json(
+        client: &rocket::local::blocking::Client,
+        uri: &str,
+        body: serde_json::Value,
+    ) -> (Status, serde_json::Value) {
+        let response = client
+            .post(uri.to_string())
+            .header(ContentType::JSON)
+            .header(Header::new("X-API-Key", memory::API_KEY))
+            .body(body.to_string())
+            .dispatch();
+        let status = response.status();
+        let body = serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        (status, body)
+    }
+
+    fn source() -> InMemoryDataSource {
+        InMemoryDataSource::new()
+            .with_order(memory::order(0x11, OWNER, 100))
+            .with_order(memory::order(0x12, OWNER, 100))
+            .with_order(memory::order(0x13, OWNER, 100))
+            .with_quote(
+                B256::repeat_byte(0x11),
+                OrderQuote {
+                    io_ratio: ether(2000),
+                    max_output: ether(1),
+                },
+            )
+            .with_quote(
+                B256::repeat_byte(0x12),
+                OrderQuote {
+                    io_ratio: ether(3000),
+                    max_output: ether(1),
+                },
+            )
+    }
+
+    #[test]
+    fn test_swap_quote_spans_orders() {
+        let client = memory::client("/v1/swap", routes(), source());
+        let (status, body) = post_json(
+            &client,
+            "/v1/swap/quote",
+            json!({
+                "inputToken": memory::usdc().address,
+                "outputToken": memory::weth().address,
+                "outputAmount": "1500000000000000000",
+            }),
+        );
+        assert_eq!(status, Status::Ok);
+        assert_eq!(body["outputAmount"], "1500000000000000000");
+        assert_eq!(body["outputAmountFormatted"], "1.5");
+        // 1 WETH at 2000 and 0.5 WETH at 3000.
+        assert_eq!(body["estimatedInput"], "3500000000");
+        assert_eq!(body["estimatedInputFormatted"], "3500");
+        assert_eq!(body["estimatedIoRatio"], "2333.333333333333333333");
+    }
+
+    #[test]
+    fn test_swap_quote_skips_orders_whose_quote_fails() {
+        let failing = source()
+            .with_order(memory::order(0x14, OWNER, 100))
+            .with_quote_error(B256::repeat_byte(0x14));
+        let client = memory::client("/v1/swap", routes(), failing);
+        let (status, body) = post_json(
+            &client,
+            "/v1/swap/quote",
+            json!({
+                "inputToken": memory::usdc().address,
+                "outputToken": memory::weth().address,
+                "outputAmount": "1500000000000000000",
+            }),
+        );
+        assert_eq!(status, Status::Ok);
+        assert_eq!(body["estimatedInput"], "3500000000");
+
+        let client = memory::client("/v1/swap", routes(), source().with_rpc_down());
+        let (status, _) = post_json(
+            &client,
+            "/v1/swap/quote",
+            json!({
+                "inputToken": memory::usdc().address,
+                "outputToken": memory::weth().address,
+                "outputAmount": "1000000000000000000",
+            }),
+        );
+        assert_eq!(status, Status::ServiceUnavailable);
+    }
+
+    #[test]
+    fn test_swap_quote_insufficient_liquidity() {
+        let client = memory::client("/v1/swap", routes(), source());
+        let (status, body) = post_json(
+            &client,
+            "/v1/swap/quote",
+            json!({
+                "inputToken": memory::usdc().address,
+                "outputToken": memory::weth().address,
+                "outputAmount": "3000000000000000000",
+            }),
+        );
+        assert_eq!(status, Status::BadRequest);
+        assert_eq!(body["error"]["code"], "INSUFFICIENT_LIQUIDITY");
+    }
+
+    #[test]
+    fn test_swap_quote_unknown_pair() {
+        let client = memory::client("/v1/swap", routes(), source());
+        let (status, _) = post_json(
+            &client,
+            "/v1/swap/quote",
+            json!({
+                "inputToken": memory::weth().address,
+                "outputToken": memory::usdc().address,
+                "outputAmount": "1000000",
+            }),
+        );
+        assert_eq!(status, Status::BadRequest);
+    }
+
+    #[test]
+    fn test_swap_calldata() {
+        let client = memory::client("/v1/swap", routes(), source());
+        let (status, body) = post_json(
+            &client,
+            "/v1/swap/calldata",
+            json!({
+                "inputToken": memory::usdc().address,
+                "outputToken": memory::weth().address,
+                "outputAmount": "1500000000000000000",
+                "maximumIoRatio": "3000",
+            }),
+        );
+        assert_eq!(status, Status::Ok);
+        assert_eq!(
+            body["to"].as_str().unwrap().parse::<Address>().unwrap(),
+            memory::ORDERBOOK
+        );
+        assert_eq!(body["value"], "0x0");
+        assert_eq!(body["estimatedInput"], "3500000000");
+
+        let data: Bytes = body["data"].as_str().unwrap().parse().unwrap();
+        let call = IOrderBookV4::takeOrders2Call::abi_decode(&data).unwrap();
+        assert_eq!(
+            call.config.maximumInput,
+            U256::from(1_500_000_000_000_000_000u128)
+        );
+        assert_eq!(call.config.maximumIORatio, ether(3000));
+        assert_eq!(call.config.orders.len(), 2);
+
+        // The approval covers 1.5 WETH at the 3000 limit, not the estimate.
+        let approvals = body["approvals"].as_array().unwrap();
+        assert_eq!(approvals.len(), 1);
+        assert_eq!(approvals[0]["amount"], "4500000000");
+        assert_eq!(approvals[0]["amountFormatted"], "4500");
+        assert_eq!(approvals[0]["symbol"], "USDC");
+        let approval_data: Bytes = approvals[0]["approvalData"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let approve = IERC20::approveCall::abi_decode(&approval_data).unwrap();
+        assert_eq!(approve.spender, memory::ORDERBOOK);
+        assert_eq!(approve.amount, U256::from(4_500_000_000u64));
+    }
+
+    #[test]
+    fn test_swap_calldata_approval_exceeds_estimate() {
+        let client = memory::client("/v1/swap", routes(), source());
+        let (status, body) = post_json(
+            &client,
+            "/v1/swap/calldata",
+            json!({
+                "inputToken": memory::usdc().address,
+                "outputToken": memory::weth().address,
+                "outputAmount": "1000000000000000000",
+                "maximumIoRatio": "2500",
+            }),
+        );
+        assert_eq!(status, Status::Ok);
+        assert_eq!(body["estimatedInput"], "2000000000");
+        assert_eq!(body["approvals"][0]["amount"], "2500000000");
+
+        // A limit too large to price the output at is rejected, not wrapped.
+        let (status, body) = post_json(
+            &client,
+            "/v1/swap/calldata",
+            json!({
+                "inputToken": memory::usdc().address,
+                "outputToken": memory::weth().address,
+                "outputAmount": "1000000000000000000",
+                "maximumIoRatio": format!("1{}", "0".repeat(58)),
+            }),
+        );
+        assert_eq!(status, Status::BadRequest);
+        assert_eq!(body["error"]["code"], "BAD_REQUEST");
+    }
+
+    #[test]
+    fn test_swap_calldata_enforces_maximum_io_ratio() {
+        let client = memory::client("/v1/swap", routes(), source());
+        let (status, body) = post_json(
+            &client,
+            "/v1/swap/calldata",
+            json!({
+                "inputToken": memory::usdc().address,
+                "outputToken": memory::weth().address,
+                "outputAmount": "1500000000000000000",
+                "maximumIoRatio": "2500",
+            }),
+        );
+        assert_eq!(status, Status::BadRequest);
+        assert_eq!(body["error"]["code"], "INSUFFICIENT_LIQUIDITY");
+
+        let (status, _) = post_json(
+            &client,
+            "/v1/swap/calldata",
+            json!({
+                "inputToken": memory::usdc().address,
+                "outputToken": memory::weth().address,
+                "outputAmount": "1000000000000000000",
+                "maximumIoRatio": "-1",
+            }),
+        );
+        assert_eq!(status, Status::BadRequest);
+    }
+
+    #[test]
+    fn test_swap_quote_rejects_overflowing_output_amount() {
+        let source = source().with_quote(
+            B256::repeat_byte(0x13),
+            OrderQuote {
+                io_ratio: ether(2500),
+                max_output: U256::MAX,
+            },
+        );
+        let client = memory::client("/v1/swap", routes(), source);
+        let (status, body) = post_json(
+            &client,
+            "/v1/swap/quote",
+            json!({
+                "inputToken": memory::usdc().address,
+                "outputToken": memory::weth().address,
+                "outputAmount": (U256::MAX - U256::from(1)).to_string(),
+            }),
+        );
+        assert_eq!(status, Status::BadRequest);
+        assert_eq!(body["error"]["code"], "BAD_REQUEST");
+    }
+
+    #[test]
+    fn test_swap_quote_rejects_invalid_request() {
+        let client = memory::client("/v1/swap", routes(), source());
+        for (input, amount) in [
+            (memory::weth().address, "1000000"),
+            (memory::usdc().address, "0"),
+            (memory::usdc().address, "abc"),
+            (memory::usdc().address, "-5"),
+        ] {
+            let (status, _) = post_json(
+                &client,
+                "/v1/swap/quote",
+                json!({
+                    "inputToken": input,
+                    "outputToken": memory::weth().address,
+                    "outputAmount": amount,
+                }),
+            );
+            assert_eq!(status, Status::BadRequest, "{input} {amount}");
+        }
+    }
+}
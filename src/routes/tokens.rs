@@ -1,7 +1,8 @@
 use crate::error::{ApiError, ApiErrorResponse};
+use crate::token_registry::TokenRegistry;
 use crate::types::tokens::TokenListResponse;
 use rocket::serde::json::Json;
-use rocket::Route;
+use rocket::{Route, State};
 
 #[utoipa::path(
     get,
@@ -13,10 +14,50 @@ use rocket::Route;
     )
 )]
 #[get("/")]
-pub async fn get_tokens() -> Result<Json<TokenListResponse>, ApiError> {
-    todo!()
+pub async fn get_tokens(
+    registry: &State<TokenRegistry>,
+) -> Result<Json<TokenListResponse>, ApiError> {
+    Ok(Json(TokenListResponse {
+        tokens: registry.tokens().to_vec(),
+    }))
 }
 
 pub fn routes() -> Vec<Route> {
     rocket::routes![get_tokens]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+
+    const REGISTRY: &str = r#"
+        [[tokens]]
+        chain_id = 8453
+        address = "0x1111111111111111111111111111111111111111"
+        symbol = "tAAPL"
+        name = "Tokenized Apple"
+        isin = "US0378331005"
+        decimals = 18
+    "#;
+
+    #[test]
+    fn test_get_tokens_returns_registry() {
+        let registry = TokenRegistry::from_toml_str(8453, REGISTRY).unwrap();
+        let rocket = rocket::build()
+            .mount("/v1/tokens", routes())
+            .manage(registry);
+        let client = Client::tracked(rocket).expect("valid rocket instance");
+
+        let response = client.get("/v1/tokens").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let body: serde_json::Value =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        let tokens = body["tokens"].as_array().unwrap();
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0]["symbol"], "tAAPL");
+        assert_eq!(tokens[0]["ISIN"], "US0378331005");
+        assert_eq!(tokens[0]["decimals"], 18);
+    }
+}
@@ -1,8 +1,68 @@
+use crate::auth::{Authorized, ReadScope};
+use crate::data_source::{
+    missing_transaction_error, OrderbookDataSource, PageRequest, TimeRange, TradeLeg, TradeRecord,
+};
+use crate::decimal;
 use crate::error::{ApiError, ApiErrorResponse};
-use crate::types::common::{ValidatedAddress, ValidatedFixedBytes};
-use crate::types::trades::{TradesByAddressResponse, TradesByTxResponse, TradesPaginationParams};
+use crate::types::common::{Amount, Ratio, TokenRef, ValidatedAddress, ValidatedFixedBytes};
+use crate::types::trades::{
+    TradeByAddress, TradeByTxEntry, TradeRequest, TradeResult, TradesByAddressResponse,
+    TradesByTxResponse, TradesPagination, TradesPaginationParams, TradesTotals,
+};
+use alloy::primitives::U256;
 use rocket::serde::json::Json;
-use rocket::Route;
+use rocket::{Route, State};
+
+fn trade_ratio(trade: &TradeRecord) -> U256 {
+    decimal::io_ratio(
+        trade.input.amount,
+        trade.input.token.decimals,
+        trade.output.amount,
+        trade.output.token.decimals,
+    )
+    .unwrap_or_default()
+}
+
+/// Sums one side of the trades in base units of its token, returning the sum
+/// and that token's decimals. `None` when the side spans more than one token,
+/// since amounts of different tokens do not add up to anything.
+fn side_total<'a>(legs: impl Iterator<Item = &'a TradeLeg>) -> Option<(U256, u8)> {
+    let mut token: Option<&TokenRef> = None;
+    let mut total = U256::ZERO;
+    for leg in legs {
+        if token.is_some_and(|token| token.address != leg.token.address) {
+            return None;
+        }
+        token = Some(&leg.token);
+        total = total.checked_add(leg.amount)?;
+    }
+    Some((total, token.map_or(0, |token| token.decimals)))
+}
+
+/// Totals are reported in base units of each side's token and omitted for a
+/// side that mixes tokens. The average IO ratio is weighted by volume (total
+/// input over total output in human units), so it needs both totals.
+fn trades_totals(trades: &[TradeRecord]) -> TradesTotals {
+    let input = side_total(trades.iter().map(|trade| &trade.input));
+    let output = side_total(trades.iter().map(|trade| &trade.output));
+    let average_io_ratio = input.zip(output).map(
+        |((total_input, input_decimals), (total_output, output_decimals))| {
+            Ratio(
+                decimal::io_ratio(total_input, input_decimals, total_output, output_decimals)
+                    .unwrap_or_default(),
+            )
+        },
+    );
+    TradesTotals {
+        total_input_amount: input.map(|(total, _)| Amount(total)),
+        total_input_amount_formatted: input
+            .map(|(total, decimals)| Amount(total).formatted(decimals)),
+        total_output_amount: output.map(|(total, _)| Amount(total)),
+        total_output_amount_formatted: output
+            .map(|(total, decimals)| Amount(total).formatted(decimals)),
+        average_io_ratio,
+    }
+}
 
 #[utoipa::path(
     get,
@@ -11,19 +71,69 @@ use rocket::Route;
     params(
         ("tx_hash" = String, Path, description = "Transaction hash"),
     ),
+    security(("api_key" = ["read"]), ("bearer" = ["read"])),
     responses(
         (status = 200, description = "Trades from transaction", body = TradesByTxResponse),
         (status = 202, description = "Transaction not yet indexed", body = ApiErrorResponse),
+        (status = 401, description = "Missing or invalid API key", body = ApiErrorResponse),
+        (status = 403, description = "API key lacks the required scope", body = ApiErrorResponse),
         (status = 404, description = "Transaction not found", body = ApiErrorResponse),
+        (status = 422, description = "Malformed path or query parameter", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit exceeded", body = ApiErrorResponse),
         (status = 500, description = "Internal server error", body = ApiErrorResponse),
+        (status = 503, description = "Upstream data source unavailable", body = ApiErrorResponse),
     )
 )]
 #[get("/tx/<tx_hash>")]
 pub async fn get_trades_by_tx(
+    auth: Result<Authorized<ReadScope>, ApiError>,
     tx_hash: ValidatedFixedBytes,
+    data_source: &State<Box<dyn OrderbookDataSource>>,
 ) -> Result<Json<TradesByTxResponse>, ApiError> {
-    let _ = tx_hash;
-    todo!()
+    auth?;
+    let Some(result) = data_source.trades_by_tx(tx_hash.0).await? else {
+        return Err(missing_transaction_error(data_source.as_ref(), tx_hash.0).await);
+    };
+    let totals = trades_totals(&result.trades);
+    let trades = result
+        .trades
+        .iter()
+        .map(|trade| {
+            let ratio = trade_ratio(trade);
+            let limits = result.limits.as_ref();
+            let maximum_input = limits.map(|limits| Amount(limits.maximum_input));
+            TradeByTxEntry {
+                order_hash: trade.order_hash,
+                order_owner: trade.order_owner,
+                request: TradeRequest {
+                    input_token: trade.input.token.address,
+                    output_token: trade.output.token.address,
+                    maximum_input,
+                    maximum_input_formatted: maximum_input
+                        .map(|amount| amount.formatted(trade.output.token.decimals)),
+                    maximum_io_ratio: limits.map(|limits| Ratio(limits.maximum_io_ratio)),
+                },
+                result: TradeResult {
+                    input_amount: Amount(trade.input.amount),
+                    input_amount_formatted: Amount(trade.input.amount)
+                        .formatted(trade.input.token.decimals),
+                    output_amount: Amount(trade.output.amount),
+                    output_amount_formatted: Amount(trade.output.amount)
+                        .formatted(trade.output.token.decimals),
+                    actual_io_ratio: Ratio(ratio),
+                },
+            }
+        })
+        .collect();
+
+    Ok(Json(TradesByTxResponse {
+        tx_hash: result.transaction.tx_hash,
+        block_number: result.transaction.block_number,
+        timestamp: result.transaction.timestamp,
+        sender: result.transaction.sender,
+        trades,
+        totals,
+    }))
 }
 
 #[utoipa::path(
@@ -34,21 +144,358 @@ pub async fn get_trades_by_tx(
         ("address" = String, Path, description = "Owner address"),
         TradesPaginationParams,
     ),
+    security(("api_key" = ["read"]), ("bearer" = ["read"])),
     responses(
         (status = 200, description = "Paginated list of trades", body = TradesByAddressResponse),
         (status = 400, description = "Bad request", body = ApiErrorResponse),
+        (status = 401, description = "Missing or invalid API key", body = ApiErrorResponse),
+        (status = 403, description = "API key lacks the required scope", body = ApiErrorResponse),
+        (status = 422, description = "Malformed path or query parameter", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit exceeded", body = ApiErrorResponse),
         (status = 500, description = "Internal server error", body = ApiErrorResponse),
+        (status = 503, description = "Upstream data source unavailable", body = ApiErrorResponse),
     )
 )]
 #[get("/<address>?<params..>", rank = 2)]
 pub async fn get_trades_by_address(
+    auth: Result<Authorized<ReadScope>, ApiError>,
     address: ValidatedAddress,
     params: TradesPaginationParams,
+    data_source: &State<Box<dyn OrderbookDataSource>>,
 ) -> Result<Json<TradesByAddressResponse>, ApiError> {
-    let _ = (address, params);
-    todo!()
+    auth?;
+    let page = PageRequest::from_params(params.page, params.page_size)?;
+    let range = TimeRange::new(params.start_time, params.end_time)?;
+    let result = data_source.trades_by_owner(address.0, range, page).await?;
+    let total_pages = result.total_pages(page.page_size);
+
+    Ok(Json(TradesByAddressResponse {
+        trades: result
+            .items
+            .into_iter()
+            .map(|trade| TradeByAddress {
+                tx_hash: trade.transaction.tx_hash,
+                input_amount: Amount(trade.input.amount),
+                input_amount_formatted: Amount(trade.input.amount)
+                    .formatted(trade.input.token.decimals),
+                output_amount: Amount(trade.output.amount),
+                output_amount_formatted: Amount(trade.output.amount)
+                    .formatted(trade.output.token.decimals),
+                input_token: trade.input.token,
+                output_token: trade.output.token,
+                order_hash: Some(trade.order_hash),
+                timestamp: trade.transaction.timestamp,
+                block_number: trade.transaction.block_number,
+            })
+            .collect(),
+        pagination: TradesPagination {
+            page: page.page,
+            page_size: page.page_size,
+            total_trades: result.total,
+            total_pages,
+            has_more: u64::from(page.page) < total_pages,
+        },
+    }))
 }
 
 pub fn routes() -> Vec<Route> {
     rocket::routes![get_trades_by_tx, get_trades_by_address]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source::memory::{self, InMemoryDataSource, OWNER};
+    use crate::data_source::{ChainTransaction, TakeOrdersLimits};
+    use rocket::http::{Header, Status};
+
+    fn get_json(
+        client: &rocket::local::blocking::Client,
+        uri: &str,
+    ) -> (Status, serde_json::Value) {
+        let response = client
+            .get(uri.to_string())
+            .header(Header::new("X-API-Key", memory::API_KEY))
+            .dispatch();
+        let status = response.status();
+        let body = serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        (status, body)
+    }
+
+    #[test]
+    fn test_trades_by_tx() {
+        let order = memory::order(0x11, OWNER, 100);
+        let tx = memory::tx(0x21, 200);
+        let source = InMemoryDataSource::new()
+            .with_trade(memory::trade(
+                &order,
+                tx.clone(),
+                1_000_000,
+                500_000_000_000_000,
+            ))
+            .with_limits(
+                tx.tx_hash,
+                TakeOrdersLimits {
+                    maximum_input: U256::from(600_000_000_000_000u64),
+                    maximum_io_ratio: U256::from(2_500_000_000_000_000_000_000u128),
+                },
+            );
+        let client = memory::client("/v1/trades", routes(), source);
+
+        let (status, body) = get_json(&client, &format!("/v1/trades/tx/{}", tx.tx_hash));
+        assert_eq!(status, Status::Ok);
+        assert_eq!(body["sender"], memory::TAKER.to_string());
+        let trade = &body["trades"][0];
+        assert_eq!(trade["request"]["maximumInput"], "600000000000000");
+        assert_eq!(trade["request"]["maximumInputFormatted"], "0.0006");
+        assert_eq!(trade["request"]["maximumIoRatio"], "2500");
+        assert_eq!(trade["result"]["inputAmount"], "1000000");
+        assert_eq!(trade["result"]["inputAmountFormatted"], "1");
+        assert_eq!(trade["result"]["actualIoRatio"], "2000");
+        assert_eq!(body["totals"]["totalInputAmount"], "1000000");
+        assert_eq!(body["totals"]["totalInputAmountFormatted"], "1");
+    }
+
+    #[test]
+    fn test_trades_by_tx_without_limits_omits_request_limits() {
+        let order = memory::order(0x11, OWNER, 100);
+        let tx = memory::tx(0x21, 200);
+        let source = InMemoryDataSource::new().with_trade(memory::trade(
+            &order,
+            tx.clone(),
+            1_000_000,
+            500_000_000_000_000,
+        ));
+        let client = memory::client("/v1/trades", routes(), source);
+
+        let (status, body) = get_json(&client, &format!("/v1/trades/tx/{}", tx.tx_hash));
+        assert_eq!(status, Status::Ok);
+        let request = &body["trades"][0]["request"];
+        assert!(request.get("maximumInput").is_none());
+        assert!(request.get("maximumInputFormatted").is_none());
+        assert!(request.get("maximumIoRatio").is_none());
+        assert_eq!(body["trades"][0]["result"]["actualIoRatio"], "2000");
+    }
+
+    fn leg(token: TokenRef, amount: u128) -> TradeLeg {
+        TradeLeg {
+            token,
+            amount: U256::from(amount),
+        }
+    }
+
+    fn record(input: TradeLeg, output: TradeLeg) -> TradeRecord {
+        let order = memory::order(0x11, OWNER, 100);
+        let mut trade = memory::trade(&order, memory::tx(0x21, 200), 0, 0);
+        trade.input = input;
+        trade.output = output;
+        trade
+    }
+
+    fn wbtc() -> TokenRef {
+        TokenRef {
+            address: alloy::primitives::Address::repeat_byte(0xcc),
+            symbol: "WBTC".into(),
+            decimals: 8,
+        }
+    }
+
+    #[test]
+    fn test_totals_empty() {
+        let totals = trades_totals(&[]);
+        assert_eq!(totals.total_input_amount, Some(Amount(U256::ZERO)));
+        assert_eq!(totals.total_output_amount, Some(Amount(U256::ZERO)));
+        assert_eq!(totals.average_io_ratio, Some(Ratio(U256::ZERO)));
+    }
+
+    #[test]
+    fn test_totals_mixed_decimals_single_trade() {
+        // 3000 USDC (6 decimals) for 1.5 WETH (18 decimals).
+        let totals = trades_totals(&[record(
+            leg(memory::usdc(), 3_000_000_000),
+            leg(memory::weth(), 1_500_000_000_000_000_000),
+        )]);
+        assert_eq!(totals.total_input_amount.unwrap().to_string(), "3000000000");
+        assert_eq!(
+            totals.total_output_amount.unwrap().to_string(),
+            "1500000000000000000"
+        );
+        assert_eq!(totals.average_io_ratio.unwrap().to_string(), "2000");
+    }
+
+    #[test]
+    fn test_totals_volume_weighted_average() {
+        // 1 WETH at 2000 and 3 WETH at 3000: the arithmetic mean of ratios
+        // would be 2500, the volume-weighted ratio is 11000 / 4 = 2750.
+        let totals = trades_totals(&[
+            record(
+                leg(memory::usdc(), 2_000_000_000),
+                leg(memory::weth(), 1_000_000_000_000_000_000),
+            ),
+            record(
+                leg(memory::usdc(), 9_000_000_000),
+                leg(memory::weth(), 3_000_000_000_000_000_000),
+            ),
+        ]);
+        assert_eq!(
+            totals.total_input_amount.unwrap().to_string(),
+            "11000000000"
+        );
+        assert_eq!(
+            totals.total_output_amount.unwrap().to_string(),
+            "4000000000000000000"
+        );
+        assert_eq!(totals.average_io_ratio.unwrap().to_string(), "2750");
+    }
+
+    #[test]
+    fn test_totals_small_amounts_keep_precision() {
+        // Dust trades alongside a large fill must not lose precision in the
+        // exact integer aggregation.
+        let totals = trades_totals(&[
+            record(leg(memory::usdc(), 1), leg(memory::weth(), 3)),
+            record(
+                leg(memory::usdc(), 123_456_789_012),
+                leg(memory::weth(), 987_654_321_098_765_432),
+            ),
+        ]);
+        assert_eq!(
+            totals.total_input_amount.unwrap().to_string(),
+            "123456789013"
+        );
+        assert_eq!(
+            totals.total_output_amount.unwrap().to_string(),
+            "987654321098765435"
+        );
+        assert_eq!(
+            totals.average_io_ratio.unwrap().to_string(),
+            "124999.998861599999760882"
+        );
+    }
+
+    #[test]
+    fn test_totals_side_mixing_tokens_is_omitted() {
+        // WBTC and WETH inputs cannot be summed; the USDC output side still
+        // totals, but without both sides there is no average ratio.
+        let totals = trades_totals(&[
+            record(
+                leg(wbtc(), 100_000_000),
+                leg(memory::usdc(), 60_000_000_000),
+            ),
+            record(
+                leg(memory::weth(), 1_000_000_000_000_000_000),
+                leg(memory::usdc(), 3_000_000_000),
+            ),
+        ]);
+        assert_eq!(totals.total_input_amount, None);
+        assert_eq!(totals.total_input_amount_formatted, None);
+        assert_eq!(
+            totals.total_output_amount.unwrap().to_string(),
+            "63000000000"
+        );
+        assert_eq!(
+            totals.total_output_amount_formatted.as_deref(),
+            Some("63000")
+        );
+        assert_eq!(totals.average_io_ratio, None);
+
+        let body = serde_json::to_value(&totals).unwrap();
+        assert!(body.get("totalInputAmount").is_none());
+        assert!(body.get("averageIoRatio").is_none());
+    }
+
+    #[test]
+    fn test_trades_by_tx_unknown_returns_404() {
+        let client = memory::client("/v1/trades", routes(), InMemoryDataSource::new());
+        let uri = format!("/v1/trades/tx/{}", memory::tx(0x21, 100).tx_hash);
+        let (status, _) = get_json(&client, &uri);
+        assert_eq!(status, Status::NotFound);
+    }
+
+    #[test]
+    fn test_trades_by_tx_missing_while_rpc_down_returns_503() {
+        let client = memory::client(
+            "/v1/trades",
+            routes(),
+            InMemoryDataSource::new().with_rpc_down(),
+        );
+        let uri = format!("/v1/trades/tx/{}", memory::tx(0x21, 100).tx_hash);
+        let (status, body) = get_json(&client, &uri);
+        assert_eq!(status, Status::ServiceUnavailable);
+        assert_eq!(body["error"]["code"], "SERVICE_UNAVAILABLE");
+        assert_eq!(body["error"]["message"], "upstream data source unavailable");
+    }
+
+    #[test]
+    fn test_trades_by_tx_pending_returns_202() {
+        let tx_hash = memory::tx(0x21, 100).tx_hash;
+        let source = InMemoryDataSource::new().with_chain_tx(tx_hash, ChainTransaction::Pending);
+        let client = memory::client("/v1/trades", routes(), source);
+        let (status, body) = get_json(&client, &format!("/v1/trades/tx/{tx_hash}"));
+        assert_eq!(status, Status::Accepted);
+        assert_eq!(body["error"]["code"], "NOT_YET_INDEXED");
+    }
+
+    #[test]
+    fn test_trades_by_address() {
+        let order = memory::order(0x11, OWNER, 100);
+        let source = InMemoryDataSource::new()
+            .with_trade(memory::trade(&order, memory::tx(0x21, 200), 1_000_000, 500))
+            .with_trade(memory::trade(&order, memory::tx(0x22, 300), 2_000_000, 900));
+        let client = memory::client("/v1/trades", routes(), source);
+
+        let (status, body) = get_json(&client, &format!("/v1/trades/{OWNER}?pageSize=1"));
+        assert_eq!(status, Status::Ok);
+        assert_eq!(body["trades"].as_array().unwrap().len(), 1);
+        assert_eq!(body["trades"][0]["orderHash"], order.order_hash.to_string());
+        assert_eq!(body["pagination"]["totalTrades"], 2);
+        assert_eq!(body["pagination"]["totalPages"], 2);
+        assert_eq!(body["pagination"]["hasMore"], true);
+    }
+
+    #[test]
+    fn test_trades_by_address_time_window() {
+        let order = memory::order(0x11, OWNER, 100);
+        let day = 1718452800;
+        let source = InMemoryDataSource::new()
+            .with_trade(memory::trade(&order, memory::tx(0x21, day - 1), 1, 1))
+            .with_trade(memory::trade(&order, memory::tx(0x22, day), 2, 2))
+            .with_trade(memory::trade(&order, memory::tx(0x23, day + 3600), 3, 3))
+            .with_trade(memory::trade(&order, memory::tx(0x24, day + 86400), 4, 4));
+        let client = memory::client("/v1/trades", routes(), source);
+
+        let uri = format!("/v1/trades/{OWNER}?startTime={day}&endTime={}", day + 86400);
+        let (status, body) = get_json(&client, &uri);
+        assert_eq!(status, Status::Ok);
+        let timestamps: Vec<_> = body["trades"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|trade| trade["timestamp"].as_u64().unwrap())
+            .collect();
+        assert_eq!(timestamps, vec![day + 3600, day]);
+        assert_eq!(body["pagination"]["totalTrades"], 2);
+
+        let (_, body) = get_json(&client, &format!("/v1/trades/{OWNER}?startTime={day}"));
+        assert_eq!(body["pagination"]["totalTrades"], 3);
+
+        let (_, body) = get_json(&client, &format!("/v1/trades/{OWNER}?endTime={day}"));
+        assert_eq!(body["pagination"]["totalTrades"], 1);
+    }
+
+    #[test]
+    fn test_trades_by_address_rejects_inverted_window() {
+        let client = memory::client("/v1/trades", routes(), InMemoryDataSource::new());
+        let uri = format!("/v1/trades/{OWNER}?startTime=200&endTime=100");
+        let (status, body) = get_json(&client, &uri);
+        assert_eq!(status, Status::BadRequest);
+        assert_eq!(body["error"]["code"], "BAD_REQUEST");
+    }
+
+    #[test]
+    fn test_trades_by_address_rejects_invalid_pagination() {
+        let client = memory::client("/v1/trades", routes(), InMemoryDataSource::new());
+        let (status, _) = get_json(&client, &format!("/v1/trades/{OWNER}?page=0"));
+        assert_eq!(status, Status::BadRequest);
+    }
+}
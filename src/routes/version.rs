@@ -0,0 +1,80 @@
+use crate::config::AppConfig;
+use crate::types::version::{DeployedContracts, VersionResponse};
+use rocket::serde::json::Json;
+use rocket::{Route, State};
+
+/// Commit the binary was built from, embedded by the build script.
+pub const COMMIT_SHA: &str = env!("COMMIT_SHA");
+const BUILD_TIMESTAMP: &str = env!("BUILD_TIMESTAMP");
+const BUILD_FEATURES: &str = env!("BUILD_FEATURES");
+
+#[utoipa::path(
+    get,
+    path = "/version",
+    tag = "Health",
+    responses(
+        (status = 200, description = "Build and deployment information", body = VersionResponse),
+    )
+)]
+#[get("/version")]
+pub async fn get_version(config: &State<AppConfig>) -> Json<VersionResponse> {
+    let deployment = &config.deployment;
+    Json(VersionResponse {
+        version: env!("CARGO_PKG_VERSION").into(),
+        commit_sha: COMMIT_SHA.into(),
+        build_timestamp: BUILD_TIMESTAMP.parse().unwrap_or_default(),
+        features: BUILD_FEATURES
+            .split(',')
+            .filter(|feature| !feature.is_empty())
+            .map(String::from)
+            .collect(),
+        chain_id: config.chain_id,
+        contracts: DeployedContracts {
+            orderbook: deployment.orderbook,
+            deployer: deployment.deployer,
+            interpreter: deployment.interpreter,
+            store: deployment.store,
+            sub_parser: deployment.sub_parser,
+        },
+        config_fingerprint: config.fingerprint(),
+    })
+}
+
+pub fn routes() -> Vec<Route> {
+    rocket::routes![get_version]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source::memory;
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+
+    fn client() -> Client {
+        let rocket = rocket::build()
+            .mount("/", routes())
+            .manage(memory::config());
+        Client::tracked(rocket).expect("valid rocket instance")
+    }
+
+    #[test]
+    fn test_get_version() {
+        let client = client();
+        let response = client.get("/version").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let body: serde_json::Value =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(body["commitSha"], COMMIT_SHA);
+        assert!(body["buildTimestamp"].as_u64().unwrap() > 0);
+        assert!(body["features"].is_array());
+        assert_eq!(body["chainId"], 8453);
+        let orderbook = memory::deployment().orderbook.to_string().to_lowercase();
+        assert_eq!(body["contracts"]["orderbook"], orderbook);
+        assert_eq!(
+            body["configFingerprint"],
+            memory::config().fingerprint().to_string()
+        );
+    }
+}
@@ -0,0 +1,201 @@
+//! Time-decaying DCA: every period the order sells up to its budget, with the
+//! IO ratio starting at `start_io` and decaying linearly to `floor_io` by the
+//! end of the period. Each period is a fresh auction.
+
+use super::{ValidatedOrder, DCA_MARKER};
+use crate::decimal;
+use crate::error::ApiError;
+use crate::token_registry::TokenRegistry;
+use crate::types::common::TokenRef;
+use crate::types::order::{DeployDcaOrderRequest, PeriodUnit};
+use crate::validation::{self, Violations};
+use alloy::primitives::{Address, U256};
+
+fn period_unit_seconds(unit: &PeriodUnit) -> u64 {
+    match unit {
+        PeriodUnit::Minutes => 60,
+        PeriodUnit::Hours => 60 * 60,
+        PeriodUnit::Days => 24 * 60 * 60,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DcaStrategy {
+    /// Base units of the output token sold per period.
+    pub budget: U256,
+    pub period_seconds: u64,
+    /// 18-decimal ratios.
+    pub start_io: U256,
+    pub floor_io: U256,
+    /// Unix seconds the first period starts at.
+    pub start_time: u64,
+}
+
+impl DcaStrategy {
+    pub fn from_request(
+        request: &DeployDcaOrderRequest,
+        registry: &TokenRegistry,
+        start_time: u64,
+    ) -> Result<ValidatedOrder<Self>, ApiError> {
+        let mut violations = Violations::default();
+        let tokens = validation::pair(
+            &mut violations,
+            registry,
+            request.input_token,
+            request.output_token,
+        );
+        let budget = validation::amount(
+            &mut violations,
+            "budgetAmount",
+            request.budget_amount,
+            tokens.as_ref().map(|(_, output)| output),
+        );
+        let period_seconds = u64::from(request.period)
+            .checked_mul(period_unit_seconds(&request.period_unit))
+            .filter(|seconds| *seconds > 0);
+        if period_seconds.is_none() {
+            violations.push("period", "must be positive");
+        }
+        let start_io = validation::ratio(&mut violations, "startIo", request.start_io);
+        let floor_io = validation::ratio(&mut violations, "floorIo", request.floor_io);
+        if let (Some(start_io), Some(floor_io)) = (start_io, floor_io) {
+            if floor_io > start_io {
+                violations.push("floorIo", "must not exceed startIo");
+            }
+        }
+        match (tokens, budget, period_seconds, start_io, floor_io) {
+            (
+                Some((input_token, output_token)),
+                Some(budget),
+                Some(period_seconds),
+                Some(start_io),
+                Some(floor_io),
+            ) if violations.is_empty() => Ok(ValidatedOrder {
+                input_token,
+                output_token,
+                strategy: Self {
+                    budget,
+                    period_seconds,
+                    start_io,
+                    floor_io,
+                    start_time,
+                },
+            }),
+            _ => Err(violations.into_error()),
+        }
+    }
+
+    /// The order's Rainlang. Orderbook calculations run in 18-decimal fixed
+    /// point, so the budget is rescaled from the output token's decimals.
+    pub fn rainlang(&self, output_token: &TokenRef, sub_parser: Address) -> String {
+        let budget = decimal::rescale(self.budget, output_token.decimals, decimal::RATIO_DECIMALS);
+        format!(
+            "{DCA_MARKER}
+using-words-from {sub_parser}
+
+start-time: {start_time},
+period: {period},
+budget: {budget},
+start-io: {start_io},
+floor-io: {floor_io},
+elapsed: int-sub(now() start-time),
+period-key: hash(order-hash() int-div(elapsed period)),
+progress: decimal18-div(int-mod(elapsed period) period),
+max-output: int-sub(budget get(period-key)),
+io-ratio: int-sub(start-io decimal18-mul(int-sub(start-io floor-io) progress));
+
+period-key: hash(order-hash() int-div(int-sub(now() {start_time}) {period})),
+:set(period-key int-add(get(period-key) output-vault-balance-decrease()));
+",
+            start_time = self.start_time,
+            period = self.period_seconds,
+            start_io = self.start_io,
+            floor_io = self.floor_io,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source::memory;
+    use crate::types::common::{Amount, Ratio};
+
+    fn request() -> DeployDcaOrderRequest {
+        DeployDcaOrderRequest {
+            input_token: memory::weth().address,
+            output_token: memory::usdc().address,
+            budget_amount: "1000000".parse().unwrap(),
+            period: 4,
+            period_unit: PeriodUnit::Hours,
+            start_io: "0.0005".parse().unwrap(),
+            floor_io: "0.0003".parse().unwrap(),
+            input_vault_id: None,
+            output_vault_id: None,
+        }
+    }
+
+    fn invalid_fields(request: &DeployDcaOrderRequest) -> Vec<String> {
+        match DcaStrategy::from_request(request, &memory::registry(), 0) {
+            Err(ApiError::UnprocessableEntity(fields)) => {
+                fields.into_iter().map(|f| f.field).collect()
+            }
+            other => panic!("expected validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_from_request() {
+        let order =
+            DcaStrategy::from_request(&request(), &memory::registry(), 1_700_000_000).unwrap();
+        assert_eq!(order.output_token.symbol, "USDC");
+        let strategy = order.strategy;
+        assert_eq!(strategy.budget, U256::from(1_000_000));
+        assert_eq!(strategy.period_seconds, 4 * 3600);
+        assert_eq!(strategy.start_io, U256::from(500_000_000_000_000u128));
+        assert_eq!(strategy.floor_io, U256::from(300_000_000_000_000u128));
+    }
+
+    #[test]
+    fn test_from_request_reports_every_violation() {
+        let mut bad = request();
+        bad.budget_amount = Amount(U256::ZERO);
+        bad.period = 0;
+        bad.start_io = Ratio(U256::ZERO);
+        assert_eq!(
+            invalid_fields(&bad),
+            vec!["budgetAmount", "period", "startIo"]
+        );
+
+        let mut bad = request();
+        bad.floor_io = "0.0006".parse().unwrap();
+        bad.output_token = bad.input_token;
+        assert_eq!(invalid_fields(&bad), vec!["outputToken", "floorIo"]);
+
+        let mut bad = request();
+        bad.input_token = Address::repeat_byte(0xcc);
+        assert_eq!(invalid_fields(&bad), vec!["inputToken"]);
+    }
+
+    #[test]
+    fn test_floor_may_equal_start() {
+        let mut flat = request();
+        flat.floor_io = flat.start_io;
+        assert!(DcaStrategy::from_request(&flat, &memory::registry(), 0).is_ok());
+    }
+
+    #[test]
+    fn test_rainlang_scales_budget_to_18_decimals() {
+        let order =
+            DcaStrategy::from_request(&request(), &memory::registry(), 1_700_000_000).unwrap();
+        let rainlang = order
+            .strategy
+            .rainlang(&order.output_token, Address::repeat_byte(0x5b));
+        assert!(rainlang.starts_with(DCA_MARKER));
+        assert!(rainlang.contains("budget: 1000000000000000000,"));
+        assert!(rainlang.contains("period: 14400,"));
+        assert!(rainlang.contains("start-io: 500000000000000,"));
+        assert!(rainlang.contains("floor-io: 300000000000000,"));
+        assert!(rainlang.contains("start-time: 1700000000,"));
+    }
+}
@@ -0,0 +1,196 @@
+//! Rainlang strategies deployed through this API.
+//!
+//! Every strategy source starts with a marker comment. The source travels in
+//! the order's meta, which lets indexed orders be classified again later.
+
+pub mod dca;
+pub mod solver;
+
+use crate::data_source::OrderbookDataSource;
+use crate::error::ApiError;
+use crate::orderbook::{self, EvaluableV3, IOrderBookV4, OrderConfigV3, IO};
+use crate::types::common::TokenRef;
+use crate::types::order::{DeployOrderResponse, OrderType};
+use alloy::primitives::{Address, Bytes, B256, U256};
+use alloy::sol_types::SolCall;
+use serde::{Deserialize, Serialize};
+
+pub const DCA_MARKER: &str = "/* st0x:dca */";
+pub const SOLVER_MARKER: &str = "/* st0x:solver */";
+
+/// Magic prefix of a Rain meta document (`RainMetaDocumentV1`).
+const RAIN_META_DOCUMENT_V1: u64 = 0xff0a89c674ee7874;
+/// Magic number identifying a Rainlang source item (`RainlangSourceV1`).
+const RAINLANG_SOURCE_V1: u64 = 0xff13109e41336ff2;
+
+/// Orders without the DCA marker are treated as fixed-ratio solver orders.
+pub fn order_type_from_meta(meta: Option<&[u8]>) -> OrderType {
+    let marker = DCA_MARKER.as_bytes();
+    let is_dca = meta.is_some_and(|meta| meta.windows(marker.len()).any(|window| window == marker));
+    if is_dca {
+        OrderType::Dca
+    } else {
+        OrderType::Solver
+    }
+}
+
+/// Contracts new orders are deployed against.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Deployment {
+    pub orderbook: Address,
+    /// Expression deployer whose parser compiles strategy sources.
+    pub deployer: Address,
+    pub interpreter: Address,
+    pub store: Address,
+    /// Provides orderbook words such as `output-vault-balance-decrease`.
+    pub sub_parser: Address,
+}
+
+/// A strategy whose request passed validation, with both tokens resolved.
+#[derive(Debug)]
+pub struct ValidatedOrder<S> {
+    pub input_token: TokenRef,
+    pub output_token: TokenRef,
+    pub strategy: S,
+}
+
+/// Uses the requested vault ID or picks a fresh random one.
+pub fn vault_id_or_random(vault_id: Option<U256>) -> U256 {
+    vault_id.unwrap_or_else(|| U256::from_be_bytes(B256::random().0))
+}
+
+/// Wraps a Rainlang source in a Rain meta document so the order's meta
+/// carries the source, and with it the strategy marker.
+pub fn rainlang_meta(rainlang: &str) -> Bytes {
+    let mut meta = RAIN_META_DOCUMENT_V1.to_be_bytes().to_vec();
+    // A single CBOR map: {0: payload, 1: magic, 2: content type}.
+    meta.push(0xa3);
+    meta.push(0x00);
+    cbor_header(&mut meta, 2, rainlang.len() as u64);
+    meta.extend_from_slice(rainlang.as_bytes());
+    meta.push(0x01);
+    cbor_header(&mut meta, 0, RAINLANG_SOURCE_V1);
+    meta.push(0x02);
+    let content_type = "application/octet-stream";
+    cbor_header(&mut meta, 3, content_type.len() as u64);
+    meta.extend_from_slice(content_type.as_bytes());
+    meta.into()
+}
+
+fn cbor_header(out: &mut Vec<u8>, major: u8, value: u64) {
+    let major = major << 5;
+    match value {
+        0..=23 => out.push(major | value as u8),
+        24..=0xff => out.extend_from_slice(&[major | 24, value as u8]),
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend_from_slice(&(value as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(major | 26);
+            out.extend_from_slice(&(value as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 27);
+            out.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+}
+
+/// A strategy ready to be compiled and deployed.
+pub struct OrderSpec {
+    pub rainlang: String,
+    pub input_token: TokenRef,
+    pub output_token: TokenRef,
+    pub input_vault_id: U256,
+    pub output_vault_id: U256,
+    /// Base units of the output token deposited alongside the order.
+    pub deposit: U256,
+}
+
+/// Compiles the strategy and composes `addOrder2` and the output vault
+/// deposit into a single orderbook `multicall`.
+pub async fn deploy_order(
+    data_source: &dyn OrderbookDataSource,
+    deployment: &Deployment,
+    spec: OrderSpec,
+) -> Result<DeployOrderResponse, ApiError> {
+    let bytecode = data_source
+        .parse_rainlang(deployment.deployer, &spec.rainlang)
+        .await?;
+    let add_order = IOrderBookV4::addOrder2Call {
+        config: OrderConfigV3 {
+            evaluable: EvaluableV3 {
+                interpreter: deployment.interpreter,
+                store: deployment.store,
+                bytecode,
+            },
+            validInputs: vec![IO {
+                token: spec.input_token.address,
+                decimals: spec.input_token.decimals,
+                vaultId: spec.input_vault_id,
+            }],
+            validOutputs: vec![IO {
+                token: spec.output_token.address,
+                decimals: spec.output_token.decimals,
+                vaultId: spec.output_vault_id,
+            }],
+            nonce: B256::random(),
+            secret: B256::random(),
+            meta: rainlang_meta(&spec.rainlang),
+        },
+        post: vec![],
+    };
+    let deposit = IOrderBookV4::deposit2Call {
+        token: spec.output_token.address,
+        vaultId: spec.output_vault_id,
+        amount: spec.deposit,
+        tasks: vec![],
+    };
+    let multicall = IOrderBookV4::multicallCall {
+        data: vec![add_order.abi_encode().into(), deposit.abi_encode().into()],
+    };
+    Ok(DeployOrderResponse {
+        to: deployment.orderbook,
+        data: multicall.abi_encode().into(),
+        value: U256::ZERO,
+        approvals: vec![orderbook::approval(
+            &spec.output_token,
+            deployment.orderbook,
+            spec.deposit,
+        )],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_type_from_meta() {
+        let meta = format!("\u{ff}\u{a}{DCA_MARKER}\n:;");
+        assert_eq!(order_type_from_meta(Some(meta.as_bytes())), OrderType::Dca);
+        assert_eq!(
+            order_type_from_meta(Some(b"/* other */")),
+            OrderType::Solver
+        );
+        assert_eq!(order_type_from_meta(None), OrderType::Solver);
+    }
+
+    #[test]
+    fn test_rainlang_meta_round_trips_marker() {
+        let source = format!("{DCA_MARKER}\n_ _: 1 2;:;");
+        let meta = rainlang_meta(&source);
+        assert_eq!(meta[..8], RAIN_META_DOCUMENT_V1.to_be_bytes());
+        // Map header, key 0, then a byte string with a one-byte length.
+        assert_eq!(meta[8..12], [0xa3, 0x00, 0x58, source.len() as u8]);
+        assert_eq!(&meta[12..12 + source.len()], source.as_bytes());
+        assert_eq!(order_type_from_meta(Some(&meta)), OrderType::Dca);
+    }
+
+    #[test]
+    fn test_vault_id_or_random() {
+        assert_eq!(vault_id_or_random(Some(U256::from(7))), U256::from(7));
+        assert_ne!(vault_id_or_random(None), vault_id_or_random(None));
+    }
+}
@@ -0,0 +1,109 @@
+//! Fixed-ratio limit orders: the order sells its whole output vault to any
+//! taker willing to pay `io_ratio` input per unit of output.
+
+use super::{ValidatedOrder, SOLVER_MARKER};
+use crate::error::ApiError;
+use crate::token_registry::TokenRegistry;
+use crate::types::order::DeploySolverOrderRequest;
+use crate::validation::{self, Violations};
+use alloy::primitives::U256;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolverStrategy {
+    /// Base units of the output token deposited for sale.
+    pub amount: U256,
+    /// 18-decimal ratio.
+    pub io_ratio: U256,
+}
+
+impl SolverStrategy {
+    pub fn from_request(
+        request: &DeploySolverOrderRequest,
+        registry: &TokenRegistry,
+    ) -> Result<ValidatedOrder<Self>, ApiError> {
+        let mut violations = Violations::default();
+        let tokens = validation::pair(
+            &mut violations,
+            registry,
+            request.input_token,
+            request.output_token,
+        );
+        let amount = validation::amount(
+            &mut violations,
+            "amount",
+            request.amount,
+            tokens.as_ref().map(|(_, output)| output),
+        );
+        let io_ratio = validation::ratio(&mut violations, "ioRatio", request.io_ratio);
+        match (tokens, amount, io_ratio) {
+            (Some((input_token, output_token)), Some(amount), Some(io_ratio))
+                if violations.is_empty() =>
+            {
+                Ok(ValidatedOrder {
+                    input_token,
+                    output_token,
+                    strategy: Self { amount, io_ratio },
+                })
+            }
+            _ => Err(violations.into_error()),
+        }
+    }
+
+    /// The order's Rainlang. The output vault balance is the only limit on
+    /// how much the order gives.
+    pub fn rainlang(&self) -> String {
+        format!(
+            "{SOLVER_MARKER}
+max-output: max-value(),
+io-ratio: {io_ratio};
+:;
+",
+            io_ratio = self.io_ratio,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source::memory;
+    use crate::types::common::{Amount, Ratio};
+
+    fn request() -> DeploySolverOrderRequest {
+        DeploySolverOrderRequest {
+            input_token: memory::usdc().address,
+            output_token: memory::weth().address,
+            amount: "1000000".parse().unwrap(),
+            io_ratio: "0.0005".parse().unwrap(),
+            input_vault_id: None,
+            output_vault_id: None,
+        }
+    }
+
+    #[test]
+    fn test_from_request() {
+        let order = SolverStrategy::from_request(&request(), &memory::registry()).unwrap();
+        assert_eq!(order.input_token.symbol, "USDC");
+        assert_eq!(order.strategy.amount, U256::from(1_000_000));
+        assert_eq!(order.strategy.io_ratio, U256::from(500_000_000_000_000u128));
+        let rainlang = order.strategy.rainlang();
+        assert!(rainlang.starts_with(SOLVER_MARKER));
+        assert!(rainlang.contains("io-ratio: 500000000000000;"));
+    }
+
+    #[test]
+    fn test_from_request_reports_every_violation() {
+        let request = DeploySolverOrderRequest {
+            amount: Amount(U256::ZERO),
+            io_ratio: Ratio(U256::ZERO),
+            ..request()
+        };
+        match SolverStrategy::from_request(&request, &memory::registry()) {
+            Err(ApiError::UnprocessableEntity(fields)) => {
+                let fields: Vec<_> = fields.iter().map(|f| f.field.as_str()).collect();
+                assert_eq!(fields, vec!["amount", "ioRatio"]);
+            }
+            other => panic!("expected validation error, got {other:?}"),
+        }
+    }
+}
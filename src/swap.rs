@@ -0,0 +1,319 @@
+//! Routing a swap across the live orders for a token pair.
+//!
+//! Swaps are phrased from the taker's side: they pay `input_token` and receive
+//! an exact amount of `output_token`. Those are the orders' own input and
+//! output, so each order's IO ratio is already input paid per unit received.
+
+use crate::data_source::{self, OrderQuote, OrderRecord, OrderbookDataSource};
+use crate::decimal;
+use crate::error::ApiError;
+use crate::orderbook::{IOrderBookV4, OrderV3, TakeOrderConfigV3, TakeOrdersConfigV3};
+use alloy::primitives::{Address, Bytes, U256};
+use alloy::sol_types::{SolCall, SolValue};
+
+#[derive(Debug, Clone)]
+pub struct SwapRoute {
+    /// Orders in the sequence they should be taken, cheapest first.
+    pub orders: Vec<OrderRecord>,
+    /// Base units of the input token owed, rounded up per order.
+    pub total_input: U256,
+    pub total_output: U256,
+    /// Volume-weighted ratio across all fills.
+    pub io_ratio: U256,
+}
+
+impl SwapRoute {
+    /// Input and output token decimals, shared by every order in the route.
+    pub fn decimals(&self) -> (u8, u8) {
+        self.orders.first().map_or((0, 0), |first| {
+            (first.input.token.decimals, first.output.token.decimals)
+        })
+    }
+}
+
+fn overflow() -> ApiError {
+    ApiError::BadRequest("outputAmount is too large to quote at current prices".into())
+}
+
+/// Fills `output_amount` from the cheapest quoted orders first, ignoring
+/// orders priced above `maximum_io_ratio`.
+pub fn route(
+    mut candidates: Vec<(OrderRecord, OrderQuote)>,
+    output_amount: U256,
+    maximum_io_ratio: Option<U256>,
+) -> Result<SwapRoute, ApiError> {
+    if let Some(maximum) = maximum_io_ratio {
+        candidates.retain(|(_, quote)| quote.io_ratio <= maximum);
+    }
+    candidates.sort_by_key(|(_, quote)| quote.io_ratio);
+    let mut remaining = output_amount;
+    let mut orders = Vec::new();
+    let mut total_input = U256::ZERO;
+    for (order, quote) in candidates {
+        if remaining.is_zero() {
+            break;
+        }
+        let output_decimals = order.output.token.decimals;
+        let available =
+            decimal::rescale(quote.max_output, decimal::RATIO_DECIMALS, output_decimals);
+        let output = available.min(remaining);
+        if output.is_zero() {
+            continue;
+        }
+        let input = decimal::input_for_output(
+            output,
+            output_decimals,
+            quote.io_ratio,
+            order.input.token.decimals,
+        )
+        .ok_or_else(overflow)?;
+        remaining -= output;
+        total_input = total_input.checked_add(input).ok_or_else(overflow)?;
+        orders.push(order);
+    }
+    if !remaining.is_zero() {
+        return Err(ApiError::InsufficientLiquidity(format!(
+            "only {} of {output_amount} output available",
+            output_amount - remaining
+        )));
+    }
+    let io_ratio = match orders.first() {
+        Some(first) => decimal::io_ratio(
+            total_input,
+            first.input.token.decimals,
+            output_amount,
+            first.output.token.decimals,
+        )
+        .ok_or_else(overflow)?,
+        None => U256::ZERO,
+    };
+    Ok(SwapRoute {
+        orders,
+        total_input,
+        total_output: output_amount,
+        io_ratio,
+    })
+}
+
+/// Quotes every active order for the pair and routes `output_amount` across
+/// them. Orders that cannot currently be quoted, or whose quote fails, are
+/// skipped.
+pub async fn quote_route(
+    data_source: &dyn OrderbookDataSource,
+    input_token: Address,
+    output_token: Address,
+    output_amount: U256,
+    maximum_io_ratio: Option<U256>,
+) -> Result<SwapRoute, ApiError> {
+    let orders = data_source
+        .orders_by_pair(input_token, output_token)
+        .await?;
+    let mut candidates = Vec::with_capacity(orders.len());
+    let mut failure = None;
+    for (order, quote) in data_source::quote_orders(data_source, orders).await {
+        match quote {
+            Ok(Some(quote)) => candidates.push((order, quote)),
+            Ok(None) => {}
+            Err(e) => {
+                warn!("skipping order {}: quote failed: {}", order.order_hash, e);
+                failure = Some(e);
+            }
+        }
+    }
+    // With no quote succeeding at all, the node is down rather than the
+    // pair being short of liquidity.
+    if let (true, Some(e)) = (candidates.is_empty(), failure) {
+        return Err(e.into());
+    }
+    route(candidates, output_amount, maximum_io_ratio)
+}
+
+/// The most input the route can cost once prices move, as bounded by
+/// `maximum_io_ratio`. Orders may be re-priced before the transaction lands,
+/// so this, not the estimate, is what the taker has to approve.
+pub fn maximum_input(route: &SwapRoute, maximum_io_ratio: U256) -> Result<U256, ApiError> {
+    let (input_decimals, output_decimals) = route.decimals();
+    decimal::input_for_output(
+        route.total_output,
+        output_decimals,
+        maximum_io_ratio,
+        input_decimals,
+    )
+    .ok_or_else(overflow)
+}
+
+/// `takeOrders2` calldata for exactly the route's output, along with the
+/// orderbook it must be sent to. The contract itself enforces
+/// `maximum_io_ratio` again at execution time.
+pub fn take_orders_call(
+    route: &SwapRoute,
+    maximum_io_ratio: U256,
+) -> Result<(Address, Bytes), ApiError> {
+    let Some(orderbook) = route.orders.first().map(|order| order.orderbook) else {
+        return Err(ApiError::BadRequest("no orders to take".into()));
+    };
+    if route
+        .orders
+        .iter()
+        .any(|order| order.orderbook != orderbook)
+    {
+        return Err(ApiError::BadRequest(
+            "liquidity for this pair is split across orderbooks".into(),
+        ));
+    }
+    let orders = route
+        .orders
+        .iter()
+        .map(|order| {
+            let decoded = OrderV3::abi_decode(&order.order_bytes).map_err(|e| {
+                ApiError::Internal(format!("invalid order {}: {e}", order.order_hash))
+            })?;
+            Ok(TakeOrderConfigV3 {
+                order: decoded,
+                inputIOIndex: U256::ZERO,
+                outputIOIndex: U256::ZERO,
+                signedContext: vec![],
+            })
+        })
+        .collect::<Result<_, ApiError>>()?;
+    // The orderbook names amounts from the taker's side of the vaults, so
+    // its "input" is what the taker receives.
+    let call = IOrderBookV4::takeOrders2Call {
+        config: TakeOrdersConfigV3 {
+            minimumInput: route.total_output,
+            maximumInput: route.total_output,
+            maximumIORatio: maximum_io_ratio,
+            orders,
+            data: Bytes::new(),
+        },
+    };
+    Ok((orderbook, call.abi_encode().into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source::memory::{self, OWNER};
+
+    fn ether(value: u128) -> U256 {
+        U256::from(value) * U256::from(10).pow(U256::from(18))
+    }
+
+    fn quoted(byte: u8, io_ratio: u128, max_output: U256) -> (OrderRecord, OrderQuote) {
+        (
+            memory::order(byte, OWNER, 100),
+            OrderQuote {
+                io_ratio: ether(io_ratio),
+                max_output,
+            },
+        )
+    }
+
+    #[test]
+    fn test_route_single_order() {
+        let route = route(
+            vec![quoted(0x11, 2000, ether(1))],
+            ether(1) / U256::from(2),
+            None,
+        )
+        .unwrap();
+        assert_eq!(route.orders.len(), 1);
+        assert_eq!(route.total_input, U256::from(1_000_000_000));
+        assert_eq!(decimal::format_ratio(route.io_ratio), "2000");
+    }
+
+    #[test]
+    fn test_route_fills_cheapest_first_across_orders() {
+        let candidates = vec![
+            quoted(0x11, 3000, ether(5)),
+            quoted(0x12, 2000, ether(1)),
+            quoted(0x13, 2500, ether(1)),
+        ];
+        let route = route(candidates, ether(3), None).unwrap();
+        let hashes: Vec<_> = route
+            .orders
+            .iter()
+            .map(|order| order.order_hash[0])
+            .collect();
+        assert_eq!(hashes, vec![0x12, 0x13, 0x11]);
+        // 2000 + 2500 + 3000 USDC for 3 WETH.
+        assert_eq!(route.total_input, U256::from(7_500_000_000u64));
+        assert_eq!(route.total_output, ether(3));
+        assert_eq!(decimal::format_ratio(route.io_ratio), "2500");
+    }
+
+    #[test]
+    fn test_route_skips_empty_orders() {
+        let candidates = vec![quoted(0x11, 1000, U256::ZERO), quoted(0x12, 2000, ether(1))];
+        let route = route(candidates, ether(1), None).unwrap();
+        assert_eq!(route.orders.len(), 1);
+        assert_eq!(route.orders[0].order_hash[0], 0x12);
+    }
+
+    #[test]
+    fn test_route_insufficient_liquidity() {
+        let candidates = vec![quoted(0x11, 2000, ether(1)), quoted(0x12, 2500, ether(1))];
+        let err = route(candidates, ether(3), None).unwrap_err();
+        assert!(matches!(err, ApiError::InsufficientLiquidity(msg) if msg.starts_with("only 2")));
+        assert!(route(vec![], ether(1), None).is_err());
+    }
+
+    #[test]
+    fn test_route_rejects_overflowing_input() {
+        let candidates = vec![quoted(0x11, 2000, U256::MAX)];
+        let err = route(candidates, U256::MAX - U256::from(1), None).unwrap_err();
+        assert!(matches!(err, ApiError::BadRequest(_)));
+    }
+
+    #[test]
+    fn test_route_respects_maximum_io_ratio() {
+        let candidates = || vec![quoted(0x11, 2000, ether(1)), quoted(0x12, 3000, ether(5))];
+        let route = route(candidates(), ether(1), Some(ether(2000))).unwrap();
+        assert_eq!(route.orders[0].order_hash[0], 0x11);
+        assert!(super::route(candidates(), ether(2), Some(ether(2999))).is_err());
+    }
+
+    #[test]
+    fn test_take_orders_call_round_trip() {
+        let candidates = vec![quoted(0x11, 2000, ether(1)), quoted(0x12, 3000, ether(5))];
+        let route = route(candidates, ether(2), None).unwrap();
+        let (to, data) = take_orders_call(&route, ether(3000)).unwrap();
+        assert_eq!(to, memory::ORDERBOOK);
+
+        let call = IOrderBookV4::takeOrders2Call::abi_decode(&data).unwrap();
+        assert_eq!(call.config.minimumInput, ether(2));
+        assert_eq!(call.config.maximumInput, ether(2));
+        assert_eq!(call.config.maximumIORatio, ether(3000));
+        let nonces: Vec<_> = call
+            .config
+            .orders
+            .iter()
+            .map(|taken| taken.order.nonce[0])
+            .collect();
+        assert_eq!(nonces, vec![0x11, 0x12]);
+        assert_eq!(call.config.orders[0].order.owner, OWNER);
+    }
+
+    #[test]
+    fn test_maximum_input_uses_maximum_io_ratio() {
+        let candidates = vec![quoted(0x11, 2000, ether(1)), quoted(0x12, 3000, ether(5))];
+        let route = route(candidates, ether(2), None).unwrap();
+        assert_eq!(route.total_input, U256::from(5_000_000_000u64));
+        assert_eq!(
+            maximum_input(&route, ether(3500)).unwrap(),
+            U256::from(7_000_000_000u64)
+        );
+        assert!(matches!(
+            maximum_input(&route, U256::MAX),
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_take_orders_call_rejects_split_orderbooks() {
+        let mut other = quoted(0x12, 3000, ether(5));
+        other.0.orderbook = Address::repeat_byte(0x0e);
+        let route = route(vec![quoted(0x11, 2000, ether(1)), other], ether(2), None).unwrap();
+        assert!(take_orders_call(&route, ether(3000)).is_err());
+    }
+}
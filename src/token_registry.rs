@@ -0,0 +1,343 @@
+use crate::config::AppConfig;
+use crate::types::tokens::TokenInfo;
+use alloy::primitives::Address;
+use rocket::fairing::AdHoc;
+use rocket::figment::providers::{Format, Toml};
+use rocket::figment::Figment;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, thiserror::Error)]
+pub enum TokenRegistryError {
+    #[error("failed to read token registry {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("failed to parse token registry: {0}")]
+    Parse(#[from] Box<rocket::figment::Error>),
+    #[error("duplicate token address {address} on chain {chain_id}")]
+    DuplicateAddress { chain_id: u64, address: Address },
+    #[error("duplicate token symbol {symbol} on chain {chain_id}")]
+    DuplicateSymbol { chain_id: u64, symbol: String },
+    #[error("invalid ISIN {isin} for token {symbol}")]
+    InvalidIsin { symbol: String, isin: String },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenEntry {
+    pub chain_id: u64,
+    pub address: Address,
+    pub symbol: String,
+    pub name: String,
+    pub isin: String,
+    pub decimals: u8,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenRegistryFile {
+    #[serde(default)]
+    tokens: Vec<TokenEntry>,
+}
+
+/// Supported tokens for the chain this server is configured for.
+#[derive(Debug, Clone)]
+pub struct TokenRegistry {
+    tokens: Vec<TokenInfo>,
+}
+
+impl TokenRegistry {
+    /// Validates every entry (across all chains) and keeps those for `chain_id`.
+    pub fn from_entries(
+        chain_id: u64,
+        entries: Vec<TokenEntry>,
+    ) -> Result<Self, TokenRegistryError> {
+        let mut addresses = HashSet::new();
+        let mut symbols = HashSet::new();
+        for entry in &entries {
+            if !addresses.insert((entry.chain_id, entry.address)) {
+                return Err(TokenRegistryError::DuplicateAddress {
+                    chain_id: entry.chain_id,
+                    address: entry.address,
+                });
+            }
+            if !symbols.insert((entry.chain_id, entry.symbol.to_uppercase())) {
+                return Err(TokenRegistryError::DuplicateSymbol {
+                    chain_id: entry.chain_id,
+                    symbol: entry.symbol.clone(),
+                });
+            }
+            if !is_valid_isin(&entry.isin) {
+                return Err(TokenRegistryError::InvalidIsin {
+                    symbol: entry.symbol.clone(),
+                    isin: entry.isin.clone(),
+                });
+            }
+        }
+
+        let tokens = entries
+            .into_iter()
+            .filter(|entry| entry.chain_id == chain_id)
+            .map(|entry| TokenInfo {
+                address: entry.address,
+                symbol: entry.symbol,
+                name: entry.name,
+                isin: entry.isin,
+                decimals: entry.decimals,
+            })
+            .collect();
+
+        Ok(Self { tokens })
+    }
+
+    pub fn from_toml_str(chain_id: u64, toml: &str) -> Result<Self, TokenRegistryError> {
+        let file: TokenRegistryFile = Figment::from(Toml::string(toml))
+            .extract()
+            .map_err(Box::new)?;
+        Self::from_entries(chain_id, file.tokens)
+    }
+
+    pub fn load(path: &Path, chain_id: u64) -> Result<Self, TokenRegistryError> {
+        let contents = std::fs::read_to_string(path).map_err(|source| TokenRegistryError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::from_toml_str(chain_id, &contents)
+    }
+
+    pub fn tokens(&self) -> &[TokenInfo] {
+        &self.tokens
+    }
+
+    pub fn get(&self, address: &Address) -> Option<&TokenInfo> {
+        self.tokens.iter().find(|token| token.address == *address)
+    }
+}
+
+/// Checks the ISIN shape (country code, 9 alphanumerics, check digit) and the
+/// Luhn check digit computed over the letter-expanded digits.
+fn is_valid_isin(isin: &str) -> bool {
+    let bytes = isin.as_bytes();
+    if bytes.len() != 12
+        || !bytes[..2].iter().all(u8::is_ascii_uppercase)
+        || !bytes[2..11]
+            .iter()
+            .all(|b| b.is_ascii_digit() || b.is_ascii_uppercase())
+        || !bytes[11].is_ascii_digit()
+    {
+        return false;
+    }
+
+    let digits: Vec<u32> = bytes
+        .iter()
+        .flat_map(|b| {
+            let value = (*b as char).to_digit(36).unwrap_or(0);
+            if value >= 10 {
+                vec![value / 10, value % 10]
+            } else {
+                vec![value]
+            }
+        })
+        .collect();
+
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, digit)| {
+            if i % 2 == 1 {
+                let doubled = digit * 2;
+                doubled / 10 + doubled % 10
+            } else {
+                *digit
+            }
+        })
+        .sum();
+
+    sum.is_multiple_of(10)
+}
+
+pub fn fairing() -> AdHoc {
+    AdHoc::try_on_ignite("Token registry", |rocket| async move {
+        let Some(config) = rocket.state::<AppConfig>() else {
+            error!("token registry needs the configuration fairing");
+            return Err(rocket);
+        };
+        match TokenRegistry::load(&config.token_registry, config.chain_id) {
+            Ok(registry) => Ok(rocket.manage(registry)),
+            Err(e) => {
+                error!("{}", e);
+                Err(rocket)
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REGISTRY: &str = r#"
+        [[tokens]]
+        chain_id = 8453
+        address = "0x1111111111111111111111111111111111111111"
+        symbol = "tAAPL"
+        name = "Tokenized Apple"
+        isin = "US0378331005"
+        decimals = 18
+
+        [[tokens]]
+        chain_id = 8453
+        address = "0x2222222222222222222222222222222222222222"
+        symbol = "tMSFT"
+        name = "Tokenized Microsoft"
+        isin = "US5949181045"
+        decimals = 18
+
+        [[tokens]]
+        chain_id = 1
+        address = "0x1111111111111111111111111111111111111111"
+        symbol = "tAAPL"
+        name = "Tokenized Apple"
+        isin = "US0378331005"
+        decimals = 18
+    "#;
+
+    fn entry(chain_id: u64, address: &str, symbol: &str, isin: &str) -> TokenEntry {
+        TokenEntry {
+            chain_id,
+            address: address.parse().unwrap(),
+            symbol: symbol.into(),
+            name: symbol.into(),
+            isin: isin.into(),
+            decimals: 18,
+        }
+    }
+
+    #[test]
+    fn test_loads_tokens_for_configured_chain() {
+        let registry = TokenRegistry::from_toml_str(8453, REGISTRY).unwrap();
+        let symbols: Vec<_> = registry
+            .tokens()
+            .iter()
+            .map(|t| t.symbol.as_str())
+            .collect();
+        assert_eq!(symbols, vec!["tAAPL", "tMSFT"]);
+
+        let registry = TokenRegistry::from_toml_str(1, REGISTRY).unwrap();
+        assert_eq!(registry.tokens().len(), 1);
+    }
+
+    #[test]
+    fn test_rejects_duplicate_address() {
+        let entries = vec![
+            entry(
+                8453,
+                "0x1111111111111111111111111111111111111111",
+                "A",
+                "US0378331005",
+            ),
+            entry(
+                8453,
+                "0x1111111111111111111111111111111111111111",
+                "B",
+                "US5949181045",
+            ),
+        ];
+        let result = TokenRegistry::from_entries(8453, entries);
+        assert!(matches!(
+            result,
+            Err(TokenRegistryError::DuplicateAddress { chain_id: 8453, .. })
+        ));
+    }
+
+    #[test]
+    fn test_rejects_duplicate_symbol_case_insensitive() {
+        let entries = vec![
+            entry(
+                8453,
+                "0x1111111111111111111111111111111111111111",
+                "tAAPL",
+                "US0378331005",
+            ),
+            entry(
+                8453,
+                "0x2222222222222222222222222222222222222222",
+                "TAAPL",
+                "US5949181045",
+            ),
+        ];
+        let result = TokenRegistry::from_entries(8453, entries);
+        assert!(matches!(
+            result,
+            Err(TokenRegistryError::DuplicateSymbol { chain_id: 8453, .. })
+        ));
+    }
+
+    #[test]
+    fn test_rejects_duplicates_on_other_chains() {
+        let entries = vec![
+            entry(
+                1,
+                "0x1111111111111111111111111111111111111111",
+                "A",
+                "US0378331005",
+            ),
+            entry(
+                1,
+                "0x1111111111111111111111111111111111111111",
+                "B",
+                "US5949181045",
+            ),
+        ];
+        assert!(TokenRegistry::from_entries(8453, entries).is_err());
+    }
+
+    #[test]
+    fn test_rejects_malformed_isin() {
+        for isin in [
+            "US037833100",
+            "us0378331005",
+            "US0378331006",
+            "US03783310A5",
+            "",
+        ] {
+            let entries = vec![entry(
+                8453,
+                "0x1111111111111111111111111111111111111111",
+                "tAAPL",
+                isin,
+            )];
+            let result = TokenRegistry::from_entries(8453, entries);
+            assert!(
+                matches!(result, Err(TokenRegistryError::InvalidIsin { .. })),
+                "{isin} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_accepts_valid_isins() {
+        for isin in [
+            "US0378331005",
+            "US5949181045",
+            "GB0002634946",
+            "DE000BAY0017",
+        ] {
+            assert!(is_valid_isin(isin), "{isin} should be accepted");
+        }
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let result = TokenRegistry::load(Path::new("does-not-exist.toml"), 8453);
+        assert!(matches!(result, Err(TokenRegistryError::Io { .. })));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let result = TokenRegistry::from_toml_str(8453, "[[tokens]]\nchain_id = \"x\"");
+        assert!(matches!(result, Err(TokenRegistryError::Parse(_))));
+    }
+}
@@ -1,6 +1,111 @@
-use alloy::primitives::{Address, Bytes, FixedBytes};
-use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use crate::decimal;
+use alloy::primitives::{Address, Bytes, FixedBytes, U256};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use utoipa::openapi::schema::{ObjectBuilder, Schema, Type};
+use utoipa::openapi::RefOr;
+use utoipa::{PartialSchema, ToSchema};
+
+/// A token amount in base units, serialized as a decimal integer string.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Amount(pub U256);
+
+/// An 18-decimal fixed-point ratio, serialized as a decimal string without
+/// trailing zeros, e.g. `0.0005`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Ratio(pub U256);
+
+#[derive(Debug, thiserror::Error)]
+pub enum NumberError {
+    #[error("expected a non-negative integer amount in base units, got {0:?}")]
+    Amount(String),
+    #[error("expected a non-negative decimal with at most 18 fractional digits, got {0:?}")]
+    Ratio(String),
+}
+
+impl Amount {
+    /// The amount in whole tokens, e.g. `1.5` for `1500000` at 6 decimals.
+    /// Every `*Formatted` response field is rendered here.
+    pub fn formatted(self, decimals: u8) -> String {
+        decimal::format_decimal(self.0, decimals)
+    }
+}
+
+impl FromStr for Amount {
+    type Err = NumberError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        // `U256::from_str` also accepts radix prefixes, which amounts never use.
+        if value.is_empty() || !value.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(NumberError::Amount(value.into()));
+        }
+        value
+            .parse()
+            .map(Amount)
+            .map_err(|_| NumberError::Amount(value.into()))
+    }
+}
+
+impl FromStr for Ratio {
+    type Err = NumberError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        decimal::parse_ratio(value)
+            .map(Ratio)
+            .ok_or_else(|| NumberError::Ratio(value.into()))
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&decimal::format_ratio(self.0))
+    }
+}
+
+macro_rules! string_number {
+    ($ty:ty, $pattern:literal, $example:literal, $description:literal) => {
+        impl Serialize for $ty {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = String::deserialize(deserializer)?;
+                value.parse().map_err(serde::de::Error::custom)
+            }
+        }
+
+        impl PartialSchema for $ty {
+            fn schema() -> RefOr<Schema> {
+                ObjectBuilder::new()
+                    .schema_type(Type::String)
+                    .pattern(Some($pattern))
+                    .description(Some($description))
+                    .examples([$example])
+                    .into()
+            }
+        }
+
+        impl ToSchema for $ty {}
+    };
+}
+
+string_number!(Amount, "^[0-9]+$", "1000000", "Token amount in base units");
+string_number!(
+    Ratio,
+    "^[0-9]+(\\.[0-9]{1,18})?$",
+    "0.0005",
+    "Input per unit of output in human-readable units"
+);
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -20,8 +125,9 @@ pub struct Approval {
     pub token: Address,
     #[schema(value_type = String, example = "0x1234567890abcdef1234567890abcdef12345678")]
     pub spender: Address,
-    #[schema(example = "1000000")]
-    pub amount: String,
+    pub amount: Amount,
+    #[schema(example = "1")]
+    pub amount_formatted: String,
     #[schema(example = "USDC")]
     pub symbol: String,
     #[schema(value_type = String, example = "0xabcdef...")]
@@ -59,6 +165,45 @@ mod tests {
     use super::*;
     use rocket::request::FromParam;
 
+    #[test]
+    fn test_amount_round_trips_as_string() {
+        let amount: Amount = serde_json::from_str("\"1000000\"").unwrap();
+        assert_eq!(amount, Amount(U256::from(1_000_000)));
+        assert_eq!(serde_json::to_string(&amount).unwrap(), "\"1000000\"");
+    }
+
+    #[test]
+    fn test_amount_formatted_scales_by_decimals() {
+        let amount = Amount(U256::from(1_500_000));
+        assert_eq!(amount.formatted(6), "1.5");
+        assert_eq!(amount.formatted(18), "0.0000000000015");
+        assert_eq!(amount.formatted(0), "1500000");
+        assert_eq!(Amount(U256::ZERO).formatted(6), "0");
+    }
+
+    #[test]
+    fn test_amount_rejects_non_integers() {
+        for value in [
+            "\"\"", "\"-5\"", "\"1.5\"", "\"0x10\"", "\"abc\"", "1000000",
+        ] {
+            assert!(serde_json::from_str::<Amount>(value).is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn test_ratio_round_trips_as_decimal() {
+        let ratio: Ratio = serde_json::from_str("\"0.0005\"").unwrap();
+        assert_eq!(ratio, Ratio(U256::from(500_000_000_000_000u128)));
+        assert_eq!(serde_json::to_string(&ratio).unwrap(), "\"0.0005\"");
+    }
+
+    #[test]
+    fn test_ratio_rejects_excess_precision() {
+        for value in ["\"-1\"", "\"abc\"", "\"0.0000000000000000001\""] {
+            assert!(serde_json::from_str::<Ratio>(value).is_err(), "{value}");
+        }
+    }
+
     #[test]
     fn test_path_address_valid() {
         let result = ValidatedAddress::from_param("0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913");
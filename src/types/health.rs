@@ -1,8 +1,52 @@
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Ok,
+    /// Serving, but a dependency is lagging.
+    Degraded,
+    /// A critical dependency is down.
+    Unavailable,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
 pub struct HealthResponse {
     #[schema(example = "ok")]
-    pub status: String,
+    pub status: HealthStatus,
+    /// Upstream checks; only reported by readiness.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<DependencyHealth>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyStatus {
+    Up,
+    Degraded,
+    Down,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyHealth {
+    #[schema(example = "subgraph")]
+    pub name: String,
+    pub status: DependencyStatus,
+    /// Time the probe took, or the timeout if it gave up.
+    #[schema(example = 42)]
+    pub latency_ms: u64,
+    /// Chain head for `rpc`, last indexed block for `subgraph`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = 31337000)]
+    pub block_number: Option<u64>,
+    /// Blocks the index trails the chain head by.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = 2)]
+    pub block_lag: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "timed out after 2000ms")]
+    pub error: Option<String>,
 }
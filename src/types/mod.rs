@@ -5,3 +5,4 @@ pub mod orders;
 pub mod swap;
 pub mod tokens;
 pub mod trades;
+pub mod version;
@@ -1,4 +1,4 @@
-use crate::types::common::{Approval, TokenRef};
+use crate::types::common::{Amount, Approval, Ratio, TokenRef};
 use alloy::primitives::{Address, Bytes, FixedBytes, U256};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
@@ -19,15 +19,15 @@ pub struct DeployDcaOrderRequest {
     #[schema(value_type = String, example = "0x4200000000000000000000000000000000000006")]
     pub output_token: Address,
     #[schema(example = "1000000")]
-    pub budget_amount: String,
+    pub budget_amount: Amount,
     #[schema(example = 4)]
     pub period: u32,
     #[schema(example = "hours")]
     pub period_unit: PeriodUnit,
     #[schema(example = "0.0005")]
-    pub start_io: String,
+    pub start_io: Ratio,
     #[schema(example = "0.0003")]
-    pub floor_io: String,
+    pub floor_io: Ratio,
     #[schema(value_type = Option<String>)]
     pub input_vault_id: Option<U256>,
     #[schema(value_type = Option<String>)]
@@ -42,9 +42,9 @@ pub struct DeploySolverOrderRequest {
     #[schema(value_type = String, example = "0x4200000000000000000000000000000000000006")]
     pub output_token: Address,
     #[schema(example = "1000000")]
-    pub amount: String,
+    pub amount: Amount,
     #[schema(example = "0.0005")]
-    pub io_ratio: String,
+    pub io_ratio: Ratio,
     #[schema(value_type = Option<String>)]
     pub input_vault_id: Option<U256>,
     #[schema(value_type = Option<String>)]
@@ -89,7 +89,9 @@ pub struct TokenReturn {
     #[schema(example = "USDC")]
     pub symbol: String,
     #[schema(example = "1000000")]
-    pub amount: String,
+    pub amount: Amount,
+    #[schema(example = "1")]
+    pub amount_formatted: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -121,7 +123,7 @@ pub struct OrderDetailsInfo {
     #[schema(example = "dca")]
     pub type_: OrderType,
     #[schema(example = "0.0005")]
-    pub io_ratio: String,
+    pub io_ratio: Ratio,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -132,9 +134,13 @@ pub struct OrderTradeEntry {
     #[schema(value_type = String, example = "0xabcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890ab")]
     pub tx_hash: FixedBytes<32>,
     #[schema(example = "1000000")]
-    pub input_amount: String,
+    pub input_amount: Amount,
+    #[schema(example = "1")]
+    pub input_amount_formatted: String,
     #[schema(example = "500000")]
-    pub output_amount: String,
+    pub output_amount: Amount,
+    #[schema(example = "0.5")]
+    pub output_amount_formatted: String,
     #[schema(example = 1718452800)]
     pub timestamp: u64,
     #[schema(value_type = String, example = "0x1234567890abcdef1234567890abcdef12345678")]
@@ -156,11 +162,15 @@ pub struct OrderDetail {
     #[schema(value_type = String, example = "0x2")]
     pub output_vault_id: U256,
     #[schema(example = "1000000")]
-    pub input_vault_balance: String,
+    pub input_vault_balance: Amount,
+    #[schema(example = "1")]
+    pub input_vault_balance_formatted: String,
     #[schema(example = "500000")]
-    pub output_vault_balance: String,
+    pub output_vault_balance: Amount,
+    #[schema(example = "0.5")]
+    pub output_vault_balance_formatted: String,
     #[schema(example = "0.0005")]
-    pub io_ratio: String,
+    pub io_ratio: Ratio,
     #[schema(example = 1718452800)]
     pub created_at: u64,
     #[schema(value_type = String, example = "0x1234567890abcdef1234567890abcdef12345678")]
@@ -199,7 +209,7 @@ mod tests {
     fn test_order_details_info_type_rename() {
         let info = OrderDetailsInfo {
             type_: OrderType::Dca,
-            io_ratio: "0.0005".into(),
+            io_ratio: "0.0005".parse().unwrap(),
         };
         let json = serde_json::to_string(&info).unwrap();
         assert!(json.contains("\"type\":\"dca\""));
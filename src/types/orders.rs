@@ -1,4 +1,4 @@
-use crate::types::common::TokenRef;
+use crate::types::common::{Amount, Ratio, TokenRef};
 use alloy::primitives::{Address, FixedBytes};
 use rocket::form::FromForm;
 use serde::{Deserialize, Serialize};
@@ -9,10 +9,10 @@ use utoipa::{IntoParams, ToSchema};
 #[serde(rename_all = "camelCase")]
 pub struct OrdersPaginationParams {
     #[field(name = "page")]
-    #[param(example = 1)]
+    #[param(example = 1, minimum = 1)]
     pub page: Option<u32>,
     #[field(name = "pageSize")]
-    #[param(example = 20)]
+    #[param(example = 20, minimum = 1, maximum = 100)]
     pub page_size: Option<u32>,
 }
 
@@ -26,9 +26,11 @@ pub struct OrderSummary {
     pub input_token: TokenRef,
     pub output_token: TokenRef,
     #[schema(example = "500000")]
-    pub output_vault_balance: String,
+    pub output_vault_balance: Amount,
+    #[schema(example = "0.5")]
+    pub output_vault_balance_formatted: String,
     #[schema(example = "0.0005")]
-    pub io_ratio: String,
+    pub io_ratio: Ratio,
     #[schema(example = 1718452800)]
     pub created_at: u64,
     #[schema(value_type = String, example = "0x1234567890abcdef1234567890abcdef12345678")]
@@ -1,4 +1,4 @@
-use crate::types::common::Approval;
+use crate::types::common::{Amount, Approval, Ratio};
 use alloy::primitives::{Address, Bytes, U256};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
@@ -11,7 +11,7 @@ pub struct SwapQuoteRequest {
     #[schema(value_type = String, example = "0x4200000000000000000000000000000000000006")]
     pub output_token: Address,
     #[schema(example = "1000000")]
-    pub output_amount: String,
+    pub output_amount: Amount,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -22,11 +22,15 @@ pub struct SwapQuoteResponse {
     #[schema(value_type = String, example = "0x4200000000000000000000000000000000000006")]
     pub output_token: Address,
     #[schema(example = "1000000")]
-    pub output_amount: String,
+    pub output_amount: Amount,
+    #[schema(example = "0.000000000001")]
+    pub output_amount_formatted: String,
     #[schema(example = "500000000000000")]
-    pub estimated_input: String,
+    pub estimated_input: Amount,
+    #[schema(example = "500000000")]
+    pub estimated_input_formatted: String,
     #[schema(example = "0.0005")]
-    pub estimated_io_ratio: String,
+    pub estimated_io_ratio: Ratio,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -37,9 +41,9 @@ pub struct SwapCalldataRequest {
     #[schema(value_type = String, example = "0x4200000000000000000000000000000000000006")]
     pub output_token: Address,
     #[schema(example = "1000000")]
-    pub output_amount: String,
+    pub output_amount: Amount,
     #[schema(example = "0.0006")]
-    pub maximum_io_ratio: String,
+    pub maximum_io_ratio: Ratio,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -52,6 +56,8 @@ pub struct SwapCalldataResponse {
     #[schema(value_type = String, example = "0x0")]
     pub value: U256,
     #[schema(example = "500000000000000")]
-    pub estimated_input: String,
+    pub estimated_input: Amount,
+    #[schema(example = "500000000")]
+    pub estimated_input_formatted: String,
     pub approvals: Vec<Approval>,
 }
@@ -1,3 +1,4 @@
+use crate::types::common::TokenRef;
 use alloy::primitives::Address;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
@@ -18,6 +19,16 @@ pub struct TokenInfo {
     pub decimals: u8,
 }
 
+impl From<&TokenInfo> for TokenRef {
+    fn from(token: &TokenInfo) -> Self {
+        TokenRef {
+            address: token.address,
+            symbol: token.symbol.clone(),
+            decimals: token.decimals,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TokenListResponse {
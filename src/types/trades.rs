@@ -1,4 +1,4 @@
-use crate::types::common::TokenRef;
+use crate::types::common::{Amount, Ratio, TokenRef};
 use alloy::primitives::{Address, FixedBytes};
 use rocket::form::FromForm;
 use serde::{Deserialize, Serialize};
@@ -9,14 +9,16 @@ use utoipa::{IntoParams, ToSchema};
 #[serde(rename_all = "camelCase")]
 pub struct TradesPaginationParams {
     #[field(name = "page")]
-    #[param(example = 1)]
+    #[param(example = 1, minimum = 1)]
     pub page: Option<u32>,
     #[field(name = "pageSize")]
-    #[param(example = 20)]
+    #[param(example = 20, minimum = 1, maximum = 100)]
     pub page_size: Option<u32>,
+    /// Only include trades at or after this unix timestamp
     #[field(name = "startTime")]
     #[param(example = 1718452800)]
     pub start_time: Option<u64>,
+    /// Only include trades before this unix timestamp
     #[field(name = "endTime")]
     #[param(example = 1718539200)]
     pub end_time: Option<u64>,
@@ -28,9 +30,13 @@ pub struct TradeByAddress {
     #[schema(value_type = String, example = "0xabcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890ab")]
     pub tx_hash: FixedBytes<32>,
     #[schema(example = "1000000")]
-    pub input_amount: String,
+    pub input_amount: Amount,
+    #[schema(example = "1")]
+    pub input_amount_formatted: String,
     #[schema(example = "500000")]
-    pub output_amount: String,
+    pub output_amount: Amount,
+    #[schema(example = "0.5")]
+    pub output_amount_formatted: String,
     pub input_token: TokenRef,
     pub output_token: TokenRef,
     #[schema(value_type = Option<String>)]
@@ -70,21 +76,32 @@ pub struct TradeRequest {
     pub input_token: Address,
     #[schema(value_type = String, example = "0x4200000000000000000000000000000000000006")]
     pub output_token: Address,
+    /// The taker's limits from the `takeOrders2` calldata; omitted when the
+    /// calldata could not be decoded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     #[schema(example = "1000000")]
-    pub maximum_input: String,
+    pub maximum_input: Option<Amount>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "1")]
+    pub maximum_input_formatted: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     #[schema(example = "0.0006")]
-    pub maximum_io_ratio: String,
+    pub maximum_io_ratio: Option<Ratio>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TradeResult {
     #[schema(example = "900000")]
-    pub input_amount: String,
+    pub input_amount: Amount,
+    #[schema(example = "0.9")]
+    pub input_amount_formatted: String,
     #[schema(example = "500000")]
-    pub output_amount: String,
+    pub output_amount: Amount,
+    #[schema(example = "0.5")]
+    pub output_amount_formatted: String,
     #[schema(example = "0.00055")]
-    pub actual_io_ratio: String,
+    pub actual_io_ratio: Ratio,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -101,12 +118,24 @@ pub struct TradeByTxEntry {
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TradesTotals {
+    /// Omitted when the trades' input tokens differ.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     #[schema(example = "900000")]
-    pub total_input_amount: String,
+    pub total_input_amount: Option<Amount>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "0.9")]
+    pub total_input_amount_formatted: Option<String>,
+    /// Omitted when the trades' output tokens differ.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     #[schema(example = "500000")]
-    pub total_output_amount: String,
+    pub total_output_amount: Option<Amount>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "0.5")]
+    pub total_output_amount_formatted: Option<String>,
+    /// Volume-weighted; omitted unless both totals are present.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     #[schema(example = "0.00055")]
-    pub average_io_ratio: String,
+    pub average_io_ratio: Option<Ratio>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
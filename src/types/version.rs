@@ -0,0 +1,42 @@
+use alloy::primitives::{Address, B256};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionResponse {
+    /// Crate version.
+    #[schema(example = "0.1.0")]
+    pub version: String,
+    /// Git commit the server was built from, or `unknown`.
+    #[schema(example = "2f631b8e4c1d0a9b7f3e6d5c4b3a29180f7e6d5c")]
+    pub commit_sha: String,
+    /// Unix time in seconds when the build script last ran.
+    #[schema(example = 1760572800)]
+    pub build_timestamp: u64,
+    /// Cargo features compiled in.
+    pub features: Vec<String>,
+    #[schema(example = 8453)]
+    pub chain_id: u64,
+    pub contracts: DeployedContracts,
+    /// keccak256 of the active configuration; differs whenever any setting
+    /// does.
+    #[schema(value_type = String)]
+    pub config_fingerprint: B256,
+}
+
+/// Contracts new orders are deployed against.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DeployedContracts {
+    #[schema(value_type = String)]
+    pub orderbook: Address,
+    #[schema(value_type = String)]
+    pub deployer: Address,
+    #[schema(value_type = String)]
+    pub interpreter: Address,
+    #[schema(value_type = String)]
+    pub store: Address,
+    #[schema(value_type = String)]
+    pub sub_parser: Address,
+}
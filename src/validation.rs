@@ -0,0 +1,180 @@
+//! Field validation for request bodies.
+//!
+//! Validators record problems in [`Violations`] instead of returning early,
+//! so a response lists every invalid field at once. Each validator returns
+//! `None` exactly when it recorded a violation.
+
+use crate::decimal;
+use crate::error::{ApiError, FieldError};
+use crate::token_registry::TokenRegistry;
+use crate::types::common::{Amount, Ratio, TokenRef};
+use alloy::primitives::{Address, U256};
+
+#[derive(Debug, Default)]
+pub struct Violations(Vec<FieldError>);
+
+impl Violations {
+    pub fn push(&mut self, field: &str, message: impl Into<String>) {
+        self.0.push(FieldError {
+            field: field.into(),
+            message: message.into(),
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn into_error(self) -> ApiError {
+        ApiError::UnprocessableEntity(self.0)
+    }
+}
+
+/// A supported token from the registry.
+pub fn token(
+    violations: &mut Violations,
+    registry: &TokenRegistry,
+    field: &str,
+    address: Address,
+) -> Option<TokenRef> {
+    let token = registry.get(&address).map(TokenRef::from);
+    if token.is_none() {
+        violations.push(field, format!("token {address} is not supported"));
+    }
+    token
+}
+
+/// Two distinct supported tokens. Tokens are only compared once both are
+/// known to be supported, so each field gets at most one violation.
+pub fn pair(
+    violations: &mut Violations,
+    registry: &TokenRegistry,
+    input_token: Address,
+    output_token: Address,
+) -> Option<(TokenRef, TokenRef)> {
+    let input = token(violations, registry, "inputToken", input_token);
+    let output = token(violations, registry, "outputToken", output_token);
+    let (input, output) = (input?, output?);
+    if input_token == output_token {
+        violations.push("outputToken", "must differ from inputToken");
+        return None;
+    }
+    Some((input, output))
+}
+
+/// A positive amount. With a known `token`, the amount must also survive
+/// scaling to the orderbook's 18-decimal arithmetic.
+pub fn amount(
+    violations: &mut Violations,
+    field: &str,
+    Amount(amount): Amount,
+    token: Option<&TokenRef>,
+) -> Option<U256> {
+    if amount.is_zero() {
+        violations.push(field, "must be positive");
+        return None;
+    }
+    if let Some(token) = token {
+        let scale = decimal::RATIO_DECIMALS.saturating_sub(token.decimals);
+        if amount
+            .checked_mul(U256::from(10).pow(U256::from(scale)))
+            .is_none()
+        {
+            violations.push(
+                field,
+                format!("exceeds the maximum for {} decimals", token.decimals),
+            );
+            return None;
+        }
+    }
+    Some(amount)
+}
+
+/// A positive ratio.
+pub fn ratio(violations: &mut Violations, field: &str, Ratio(ratio): Ratio) -> Option<U256> {
+    if ratio.is_zero() {
+        violations.push(field, "must be positive");
+        return None;
+    }
+    Some(ratio)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source::memory;
+
+    fn fields(violations: Violations) -> Vec<String> {
+        match violations.into_error() {
+            ApiError::UnprocessableEntity(fields) => fields.into_iter().map(|f| f.field).collect(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_pair() {
+        let registry = memory::registry();
+        let mut violations = Violations::default();
+        let pair = pair(
+            &mut violations,
+            &registry,
+            memory::usdc().address,
+            memory::weth().address,
+        );
+        assert_eq!(pair.unwrap().1.symbol, "WETH");
+        assert!(violations.is_empty());
+
+        let usdc = memory::usdc().address;
+        let mut violations = Violations::default();
+        assert!(super::pair(&mut violations, &registry, usdc, usdc).is_none());
+        assert_eq!(fields(violations), vec!["outputToken"]);
+    }
+
+    #[test]
+    fn test_pair_reports_each_field_once() {
+        let registry = memory::registry();
+        let usdc = memory::usdc().address;
+        let unknown = Address::repeat_byte(0xcc);
+        let mut violations = Violations::default();
+        assert!(pair(&mut violations, &registry, unknown, unknown).is_none());
+        assert_eq!(fields(violations), vec!["inputToken", "outputToken"]);
+
+        let mut violations = Violations::default();
+        assert!(pair(&mut violations, &registry, usdc, unknown).is_none());
+        assert_eq!(fields(violations), vec!["outputToken"]);
+    }
+
+    #[test]
+    fn test_amount() {
+        let mut violations = Violations::default();
+        let usdc = memory::usdc();
+        assert_eq!(
+            amount(
+                &mut violations,
+                "amount",
+                Amount(U256::from(1_000_000)),
+                Some(&usdc)
+            ),
+            Some(U256::from(1_000_000))
+        );
+        assert!(violations.is_empty());
+
+        assert!(amount(&mut violations, "amount", Amount(U256::ZERO), None).is_none());
+        // Scaling a 6-decimal amount to 18 decimals would overflow.
+        let huge = Amount(U256::MAX);
+        assert!(amount(&mut violations, "amount", huge, Some(&usdc)).is_none());
+        assert!(amount(&mut violations, "amount", huge, Some(&memory::weth())).is_some());
+        assert_eq!(fields(violations).len(), 2);
+    }
+
+    #[test]
+    fn test_ratio() {
+        let mut violations = Violations::default();
+        assert_eq!(
+            ratio(&mut violations, "ioRatio", "0.0005".parse().unwrap()),
+            Some(U256::from(500_000_000_000_000u128))
+        );
+        assert!(ratio(&mut violations, "ioRatio", Ratio(U256::ZERO)).is_none());
+        assert_eq!(fields(violations), vec!["ioRatio"]);
+    }
+}